@@ -1,21 +1,112 @@
+use chrono::{Local, Utc};
 use eframe::egui::{self, Color32};
 use std::env;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::internal::clock::Clock;
-use crate::internal::dirs::default_config_path;
-use crate::internal::settings_parser::AppSettings;
+use crate::internal::cli::CliArgs;
+use crate::internal::clock::{Clock, State};
+use crate::internal::dirs::{
+    default_config_path, default_profiles_dir, default_session_path, default_socket_path,
+};
+use crate::internal::history::{History, Record};
+use crate::internal::ipc::{IpcRequest, IpcServer};
+use crate::internal::json::Json;
+use crate::internal::keybindings::{Action, KeyBinding, KeyBindings};
+use crate::internal::mpris::{MprisCommand, MprisEvent, MprisPlayer, PlayerState};
+use crate::internal::mqtt::{MqttClient, MqttCommand};
+use crate::internal::notifications::{Notification, NotificationAction, Notifier, NotifierEvent};
+use crate::internal::osc::{Osc, OscArg, OscCommand, OscMessage};
+use crate::internal::profiles::{Profile, list_profiles};
+use crate::internal::script::{Script, ScriptAction, ScriptEvent};
+use crate::internal::sequence::{expand, format_sequence, parse_sequence, timers_to_sequence};
+use crate::internal::session::SessionState;
+use crate::internal::settings_parser::{AppSettings, ProgressStyle, WindowGeometry};
 use crate::internal::sound::{self, SoundFile};
-use crate::ui::countdown::{CountdownElement, CountdownSignal};
-use crate::ui::settings::{self, SettingsSignal};
+use crate::internal::statistics::Statistics;
+use crate::internal::templates::{TemplateLibrary, sanitize_name};
+use crate::internal::theme::Palette;
+use crate::internal::timer::Timer;
+use crate::internal::title::DEFAULT_TITLE_FORMAT;
+use crate::internal::webhooks::{WebhookEvent, WebhookFields, Webhooks};
+use crate::ui::countdown::{
+    ADD_TIME, CountdownElement, CountdownSignal, IntervalSummary, REMOVE_TIME,
+};
+use crate::ui::effects::Effects;
+use crate::ui::palette::{CommandPalette, PaletteSignal};
+use crate::ui::session::{SessionPanel, SessionSignal};
+use crate::ui::settings::{self, SettingsErrors, SettingsSignal};
+use crate::ui::shortcuts::{self, ShortcutsSignal};
+use crate::ui::statistics;
+use crate::ui::templates::{TemplateEditor, TemplateSignal};
+use crate::ui::theme;
+use crate::ui::webhooks::{WebhookEditor, WebhookSignal};
+use crate::ui::window_title::WindowTitle;
+
+#[derive(PartialEq, Eq)]
+enum View {
+    Countdown,
+    Settings,
+    Statistics,
+}
 
 pub struct App {
     countdown_element: CountdownElement,
     settings: AppSettings,
-    show_settings: bool,
+    view: View,
     custom_sound: sound::SoundFile,
+    history: History,
+    statistics: Option<Statistics>,
+    resume_offer: Option<SessionState>,
+    session_save_clock: Clock,
+    template_library: TemplateLibrary,
+    template_editor: TemplateEditor,
+    webhooks: Webhooks,
+    webhook_editor: WebhookEditor,
+    webhook_error: Option<String>,
+    session_started: bool,
+    profiles: Vec<String>,
+    sequence_text: String,
+    sequence_error: Option<String>,
+    palette_name: String,
+    window_title: WindowTitle,
+    show_shortcuts: bool,
+    rebinding: Option<Action>,
+    palette: Option<CommandPalette>,
+    presentation: bool,
+    mini_window: bool,
+    mini_geometry: Option<WindowGeometry>,
+    profile_style: Option<ProgressStyle>,
+    session_panel: SessionPanel,
+    effects: Effects,
+    notifier: Option<Notifier>,
+    notification_error: Option<String>,
+    notified_upcoming: Option<(usize, u32)>,
+    mpris: Option<MprisPlayer>,
+    mpris_error: Option<String>,
+    ipc: Option<IpcServer>,
+    ipc_state: Option<(bool, usize, u32)>,
+    osc: Option<Osc>,
+    osc_error: Option<String>,
+    osc_started: Option<(usize, u32)>,
+    osc_remaining_sent: Instant,
+    mqtt: Option<MqttClient>,
+    mqtt_error: Option<String>,
+    mqtt_state: Option<(bool, usize, u32)>,
+    mqtt_remaining: Option<u64>,
+    script: Option<Script>,
+    script_error: Option<String>,
+    script_started: Option<(usize, u32)>,
+    // Generated by the script's sequence(), never saved to the settings
+    script_timers: Option<Vec<Timer>>,
+    window_focused: bool,
 }
 
+const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+const MINI_WINDOW_SIZE: [f32; 2] = [220.0, 72.0];
+const RESIZE_GRIP_SIZE: f32 = 12.0;
+const WEBHOOK_FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
 const DEFAULT_TIMER_DURATION: u64 = 5000;
 const DEFAULT_TIMER_COLOR: (u8, u8, u8) = (
     Color32::RED.to_array()[0],
@@ -24,15 +115,22 @@ const DEFAULT_TIMER_COLOR: (u8, u8, u8) = (
 );
 
 impl App {
-    pub fn new(settings: AppSettings) -> Self {
-        let countdown_element = CountdownElement::new(
-            &settings
-                .timers
-                .iter()
-                .map(|v| (v.0, Color32::from_rgb(v.1.0, v.1.1, v.1.2)))
-                .collect(),
-            settings.play_once,
-        );
+    pub fn new(
+        settings: AppSettings,
+        history: History,
+        session: Option<SessionState>,
+        template_library: TemplateLibrary,
+        webhooks: Webhooks,
+        presentation: bool,
+    ) -> Self {
+        let profile_style = default_profiles_dir()
+            .ok()
+            .and_then(|dir| Profile::new_from_file(&dir, &settings.profile).ok())
+            .and_then(|profile| profile.progress_style);
+
+        let mut countdown_element =
+            CountdownElement::new(&settings.timers, settings.play_once, settings.catch_up);
+        countdown_element.set_shuffle(settings.shuffle);
 
         let mut custom_sound = SoundFile::new();
         if settings.use_custom_sound {
@@ -44,41 +142,307 @@ impl App {
                 });
         }
 
-        Self {
+        let resume_offer = session.filter(|v| v.has_progress());
+        let sequence_text = match settings.sequence.is_empty() {
+            true => timers_to_sequence(&settings.timers),
+            false => settings.sequence.clone(),
+        };
+
+        let mut app = Self {
             countdown_element,
             settings,
-            show_settings: false,
+            view: View::Countdown,
             custom_sound,
-        }
+            history,
+            statistics: None,
+            resume_offer,
+            session_save_clock: Clock::new(SESSION_SAVE_INTERVAL.as_millis() as u64),
+            template_library,
+            template_editor: TemplateEditor::new(),
+            webhooks,
+            webhook_editor: WebhookEditor::new(),
+            webhook_error: None,
+            session_started: false,
+            profiles: load_profile_names(),
+            sequence_text,
+            sequence_error: None,
+            palette_name: String::new(),
+            window_title: WindowTitle::new(),
+            show_shortcuts: false,
+            rebinding: None,
+            palette: None,
+            presentation,
+            mini_window: false,
+            mini_geometry: None,
+            profile_style,
+            session_panel: SessionPanel::new(),
+            effects: Effects::new(),
+            notifier: None,
+            notification_error: None,
+            notified_upcoming: None,
+            mpris: None,
+            mpris_error: None,
+            ipc: None,
+            ipc_state: None,
+            osc: None,
+            osc_error: None,
+            osc_started: None,
+            osc_remaining_sent: Instant::now(),
+            mqtt: None,
+            mqtt_error: None,
+            mqtt_state: None,
+            mqtt_remaining: None,
+            script: None,
+            script_error: None,
+            script_started: None,
+            script_timers: None,
+            window_focused: true,
+        };
+        app.apply_display_settings();
+        app.connect_notifier();
+        app.start_mpris();
+        app.start_osc();
+        app.start_mqtt();
+        app.load_script();
+        // A generated sequence can differ from the one that was interrupted
+        // and a shuffled one can only carry on with the round it was dealt
+        let shuffled = app.settings.shuffle.is_active();
+        app.resume_offer = app
+            .resume_offer
+            .take()
+            .filter(|v| v.timers == app.timers() && v.dealt.is_some() == shuffled);
+        app
     }
 
-    fn toggle_settings(&mut self) -> () {
-        self.show_settings = !self.show_settings;
+    // The timers being run, which a script may have generated
+    fn timers(&self) -> &[Timer] {
+        self.script_timers
+            .as_deref()
+            .unwrap_or(&self.settings.timers)
+    }
+
+    fn toggle_view(&mut self, view: View) {
+        self.view = if self.view == view {
+            View::Countdown
+        } else {
+            view
+        };
+
+        if self.view == View::Statistics {
+            let today = Local::now().date_naive();
+            self.statistics = Some(Statistics::new(self.history.records(), today));
+        }
     }
 
     fn change_countdown_element(&mut self) {
+        self.abandon_session();
         self.countdown_element = CountdownElement::new(
-            &self
-                .settings
-                .timers
-                .iter()
-                .map(|v| (v.0, Color32::from_rgb(v.1.0, v.1.1, v.1.2)))
-                .collect(),
+            self.timers(),
             self.settings.play_once,
+            self.settings.catch_up,
         );
+        self.countdown_element.set_shuffle(self.settings.shuffle);
+        self.apply_display_settings();
+    }
+
+    fn apply_display_settings(&mut self) {
+        let style = self.profile_style.unwrap_or(self.settings.progress_style);
+        self.countdown_element
+            .set_style(style, self.settings.ring_thickness);
+
+        let effects = &self.settings.effects;
+        let animation = match effects.animate && !effects.reduce_motion {
+            true => Some(Duration::from_millis(effects.duration_ms)),
+            false => None,
+        };
+        self.countdown_element.set_animation(animation);
+    }
+
+    // Timers edited by hand no longer match a saved profile or sequence
+    fn timers_edited(&mut self) {
+        self.script_timers = None;
+        self.settings.profile = String::new();
+        self.profile_style = None;
+        self.settings.sequence = String::new();
+        self.sequence_text = timers_to_sequence(&self.settings.timers);
+        self.sequence_error = None;
+        self.change_countdown_element();
+    }
+
+    fn apply_sequence(&mut self) {
+        match parse_sequence(&self.sequence_text) {
+            Ok(items) => {
+                self.script_timers = None;
+                self.settings.timers = expand(&items);
+                self.settings.profile = String::new();
+                self.settings.sequence = format_sequence(&items);
+                self.sequence_text = self.settings.sequence.clone();
+                self.sequence_error = None;
+                self.change_countdown_element();
+            }
+            Err(e) => self.sequence_error = Some(e.to_string()),
+        }
     }
 
     fn save_settings(&self) {
-        let path = default_config_path().unwrap_or(
-            // TODO: Handle this error better
-            env::temp_dir()
-                .join("config.txt")
-                .to_string_lossy()
-                .to_string(),
-        );
+        // TODO: Handle error
+        let _ = self.settings.write_to_file(&settings_path());
+    }
 
+    // For changes made outside the settings view, which should not
+    // save whatever is still being edited there
+    fn save_settings_keys(&self, keys: &[&str]) {
         // TODO: Handle error
-        let _ = self.settings.write_to_file(&path);
+        let _ = self.settings.write_keys_to_file(&settings_path(), keys);
+    }
+
+    fn save_session(&self) {
+        // TODO: Handle this error better
+        let Ok(path) = default_session_path() else {
+            return;
+        };
+
+        let session = SessionState {
+            profile: self.settings.profile.clone(),
+            timers: self.timers().to_vec(),
+            dealt: self.countdown_element.dealt_round(),
+            index: self.countdown_element.index(),
+            round: self.countdown_element.round(),
+            elapsed: self.countdown_element.elapsed(),
+            paused: self.countdown_element.is_paused(),
+        };
+
+        // TODO: Handle error
+        let _ = match session.has_progress() {
+            true => session.write_to_file(&path),
+            false => SessionState::remove_file(&path),
+        };
+    }
+
+    fn resume_session(&mut self) {
+        if let Some(session) = self.resume_offer.take() {
+            if let Some(dealt) = &session.dealt {
+                self.countdown_element.restore_round(dealt);
+            }
+            self.countdown_element.restore(
+                session.index,
+                session.round,
+                session.elapsed,
+                session.paused,
+            );
+        }
+    }
+
+    fn dismiss_session(&mut self) {
+        self.resume_offer = None;
+        self.save_session();
+    }
+
+    fn draw_resume_offer(&mut self, ui: &mut egui::Ui) {
+        let Some(session) = &self.resume_offer else {
+            return;
+        };
+
+        let mut description = format!(
+            "Interval {} of {}, round {}",
+            session.index + 1,
+            session.timers.len(),
+            session.round + 1
+        );
+        if !session.profile.is_empty() {
+            description = format!("{}: {}", session.profile, description);
+        }
+
+        ui.group(|ui| {
+            ui.label(description);
+            ui.horizontal(|ui| {
+                if ui.button("Resume where you left off").clicked() {
+                    self.resume_session();
+                }
+
+                if ui.button("Dismiss").clicked() {
+                    self.dismiss_session();
+                }
+            });
+        });
+        ui.add_space(8.0);
+    }
+
+    fn draw_debug_overlay(&self, ctx: &egui::Context) {
+        let report = self.countdown_element.drift_report();
+        let average_latency = match report.transitions {
+            0 => Duration::ZERO,
+            n => report.total_latency / n,
+        };
+
+        egui::Area::new(egui::Id::new("debug_overlay"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.monospace(format!("transitions: {}", report.transitions));
+                    ui.monospace(format!(
+                        "latency:     avg {} ms, max {} ms",
+                        average_latency.as_millis(),
+                        report.max_latency.as_millis()
+                    ));
+                    ui.monospace(format!(
+                        "absorbed:    {} ms",
+                        report.total_latency.as_millis()
+                    ));
+                });
+            });
+    }
+
+    fn save_profile(
+        &mut self,
+        name: String,
+        progress_style: Option<ProgressStyle>,
+    ) -> Result<(), String> {
+        let dir = default_profiles_dir()?;
+        let profile = Profile {
+            name,
+            timers: self.settings.timers.clone(),
+            progress_style,
+        };
+
+        profile.write_to_file(&dir)?;
+        self.settings.profile = profile.name;
+        self.profile_style = profile.progress_style;
+        self.apply_display_settings();
+        self.profiles = load_profile_names();
+        Ok(())
+    }
+
+    fn load_profile(&mut self, name: &str) -> Result<(), String> {
+        let dir = default_profiles_dir()?;
+        let profile = Profile::new_from_file(&dir, name)?;
+
+        self.settings.timers = profile.timers;
+        self.timers_edited();
+        self.settings.profile = profile.name;
+        self.profile_style = profile.progress_style;
+        self.apply_display_settings();
+        Ok(())
+    }
+
+    pub fn handle_template_signal(&mut self, signal: TemplateSignal) -> Result<(), String> {
+        match signal {
+            TemplateSignal::Apply(timers) => {
+                self.settings.timers = timers;
+                self.timers_edited();
+            }
+            TemplateSignal::SaveTemplate(template) => {
+                self.template_library.save(template)?;
+            }
+            TemplateSignal::SaveProfile(name, progress_style) => {
+                self.save_profile(name, progress_style)?;
+            }
+            TemplateSignal::LoadProfile(name) => {
+                self.load_profile(&name)?;
+            }
+        };
+        Ok(())
     }
 
     fn load_sound(&mut self) {
@@ -93,18 +457,125 @@ impl App {
         match signal {
             SettingsSignal::UpdateTimer((index, timer)) => {
                 self.settings.timers[index] = timer;
-                self.change_countdown_element();
+                self.timers_edited();
             }
             SettingsSignal::AddTimer => {
                 self.settings
                     .timers
-                    .push((DEFAULT_TIMER_DURATION, DEFAULT_TIMER_COLOR));
-                self.change_countdown_element();
+                    .push(Timer::new(DEFAULT_TIMER_DURATION, DEFAULT_TIMER_COLOR));
+                self.timers_edited();
             }
             SettingsSignal::UpdatePlayOnce(play_once) => {
                 self.settings.play_once = play_once;
-                self.change_countdown_element();
+                self.countdown_element.set_play_once(play_once);
+            }
+            SettingsSignal::UpdateKeepAdjustments(keep_adjustments) => {
+                self.settings.keep_adjustments = keep_adjustments;
+            }
+            SettingsSignal::UpdateCatchUp(catch_up) => {
+                self.settings.catch_up = catch_up;
+                self.countdown_element.set_catch_up(catch_up);
+            }
+            SettingsSignal::UpdateShuffle(shuffle) => {
+                self.settings.shuffle = shuffle;
+            }
+            SettingsSignal::ApplyShuffle => self.change_countdown_element(),
+            SettingsSignal::UpdateProgressStyle(progress_style) => {
+                self.settings.progress_style = progress_style;
+                self.apply_display_settings();
+            }
+            SettingsSignal::UpdateRingThickness(ring_thickness) => {
+                self.settings.ring_thickness = ring_thickness;
+                self.apply_display_settings();
+            }
+            SettingsSignal::UpdateEffects(effects) => {
+                self.settings.effects = effects;
+                self.apply_display_settings();
+            }
+            SettingsSignal::UpdateTheme(theme) => {
+                self.settings.theme = theme;
+            }
+            SettingsSignal::UpdateFontSize(font_size) => {
+                self.settings.font_size = font_size;
+            }
+            SettingsSignal::UpdateTimerFontScale(scale) => {
+                self.settings.timer_font_scale = scale;
+            }
+            SettingsSignal::UpdateTitleFormat(format) => {
+                self.settings.title_format = format;
+            }
+            SettingsSignal::ApplyPalette(palette) => {
+                let colors: Vec<_> = self.settings.timers.iter().map(|v| v.color).collect();
+                for (timer, color) in self
+                    .settings
+                    .timers
+                    .iter_mut()
+                    .zip(palette.recolor(&colors))
+                {
+                    timer.color = color;
+                }
+                self.timers_edited();
+            }
+            SettingsSignal::UpdatePaletteName(name) => {
+                self.palette_name = name;
+            }
+            SettingsSignal::SavePalette => {
+                let name = sanitize_name(&self.palette_name).replace(':', "");
+                let mut colors: Vec<(u8, u8, u8)> = vec![];
+                for timer in &self.settings.timers {
+                    if !colors.contains(&timer.color) {
+                        colors.push(timer.color);
+                    }
+                }
+
+                if !name.is_empty() && !colors.is_empty() {
+                    self.settings.palettes.retain(|v| v.name != name);
+                    self.settings.palettes.push(Palette { name, colors });
+                    self.palette_name = String::new();
+                }
+            }
+            SettingsSignal::DeletePalette(index) => {
+                if index < self.settings.palettes.len() {
+                    self.settings.palettes.remove(index);
+                }
+            }
+            SettingsSignal::UpdateNotifications(notifications) => {
+                self.settings.notifications = notifications;
+                self.connect_notifier();
+            }
+            SettingsSignal::UpdateNotifyBeforeSeconds(seconds) => {
+                self.settings.notify_before_seconds = seconds;
+            }
+            SettingsSignal::UpdateMpris(mpris) => {
+                self.settings.mpris = mpris;
+                self.start_mpris();
+            }
+            SettingsSignal::UpdateOsc(osc) => {
+                self.settings.osc = osc;
+                self.start_osc();
+            }
+            SettingsSignal::UpdateOscTarget(target) => {
+                self.settings.osc_target = target;
+            }
+            SettingsSignal::UpdateOscBind(bind) => {
+                self.settings.osc_bind = bind;
+            }
+            SettingsSignal::UpdateOscListenPort(port) => {
+                self.settings.osc_listen_port = port;
+            }
+            SettingsSignal::RestartOsc => self.start_osc(),
+            SettingsSignal::UpdateMqtt(mqtt) => {
+                let toggled = mqtt.enabled != self.settings.mqtt.enabled;
+                self.settings.mqtt = mqtt;
+                if toggled {
+                    self.start_mqtt();
+                }
             }
+            SettingsSignal::RestartMqtt => self.start_mqtt(),
+            SettingsSignal::UpdateScript(path) => {
+                self.settings.script = path;
+            }
+            SettingsSignal::LoadScript => self.load_script(),
             SettingsSignal::UpdateVolume(volume) => {
                 self.settings.volume = volume;
             }
@@ -115,23 +586,1020 @@ impl App {
                 self.settings.custom_sound_location = location;
                 self.load_sound();
             }
+            SettingsSignal::UpdateSequenceText(text) => {
+                self.sequence_text = text;
+            }
+            SettingsSignal::ApplySequence => {
+                self.apply_sequence();
+            }
             SettingsSignal::SaveSettings => {
                 self.save_settings();
-                self.show_settings = false;
+                self.view = View::Countdown;
             }
         };
         Ok(())
     }
 
-    pub fn handle_countdown_signal(&self, signal: CountdownSignal) {
+    fn run_action(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
+            Action::StartPause => self.countdown_element.toggle_running(),
+            Action::Previous => {
+                if let Some(signal) = self.countdown_element.previous() {
+                    self.handle_countdown_signal(signal);
+                }
+            }
+            Action::Next => {
+                if let Some(signal) = self.countdown_element.skip() {
+                    self.handle_countdown_signal(signal);
+                }
+            }
+            Action::Restart => self.countdown_element.restart(),
+            Action::AddTime => self.adjust_interval(ADD_TIME.as_millis() as i64),
+            Action::RemoveTime => self.adjust_interval(-(REMOVE_TIME.as_millis() as i64)),
+            Action::Settings | Action::Statistics if self.presentation => {
+                self.set_presentation(ctx, false);
+                self.run_action(ctx, action);
+            }
+            Action::Settings => self.toggle_view(View::Settings),
+            Action::Statistics => self.toggle_view(View::Statistics),
+            Action::Save => {
+                self.handle_settings_signal(SettingsSignal::SaveSettings)
+                    .unwrap_or_default();
+            }
+            Action::Fullscreen => {
+                let fullscreen = ctx.input(|i| i.viewport().fullscreen.unwrap_or(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(!fullscreen));
+            }
+            Action::Presentation => self.set_presentation(ctx, !self.presentation),
+            Action::MiniWindow => self.toggle_mini_window(),
+            Action::SessionPanel => {
+                self.settings.session_panel = !self.settings.session_panel;
+                self.save_settings_keys(&["session_panel"]);
+            }
+            Action::Shortcuts => {
+                self.show_shortcuts = !self.show_shortcuts;
+                self.rebinding = None;
+            }
+            Action::CommandPalette => {
+                self.palette = match self.palette {
+                    Some(_) => None,
+                    None => Some(CommandPalette::new()),
+                };
+            }
+        }
+    }
+
+    fn set_presentation(&mut self, ctx: &egui::Context, presentation: bool) {
+        self.presentation = presentation;
+        self.view = View::Countdown;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(presentation));
+    }
+
+    fn toggle_mini_window(&mut self) {
+        self.mini_window = !self.mini_window;
+
+        // Remember where the window was left for next time
+        if !self.mini_window && self.mini_geometry.is_some() {
+            self.settings.mini_window = self.mini_geometry.take();
+            self.save_settings_keys(&["mini_window"]);
+        }
+    }
+
+    fn draw_mini_window(&mut self, ctx: &egui::Context) {
+        let mut builder = egui::ViewportBuilder::default()
+            .with_title("Interval Timer")
+            .with_decorations(false)
+            .with_always_on_top()
+            .with_resizable(true)
+            .with_min_inner_size([80.0, 32.0])
+            .with_mouse_passthrough(self.settings.mini_click_through);
+        builder = match self.settings.mini_window {
+            Some(geometry) => builder
+                .with_position([geometry.x, geometry.y])
+                .with_inner_size([geometry.width, geometry.height]),
+            None => builder.with_inner_size(MINI_WINDOW_SIZE),
+        };
+
+        let viewport_id = egui::ViewportId::from_hash_of("mini_window");
+        ctx.show_viewport_immediate(viewport_id, builder, |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                // Borderless, so the whole window is the drag handle
+                let rect = ui.max_rect();
+                let drag = ui.interact(rect, ui.id().with("drag"), egui::Sense::drag());
+                if drag.drag_started() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                }
+
+                let grip = egui::Rect::from_min_max(
+                    rect.max - egui::vec2(RESIZE_GRIP_SIZE, RESIZE_GRIP_SIZE),
+                    rect.max,
+                );
+                let resize = ui.interact(grip, ui.id().with("resize"), egui::Sense::drag());
+                if resize.drag_started() {
+                    let direction = egui::viewport::ResizeDirection::SouthEast;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::BeginResize(direction));
+                }
+
+                self.countdown_element.draw_mini(ui);
+            });
+
+            let (outer, inner, close) = ctx.input(|i| {
+                let viewport = i.viewport();
+                (
+                    viewport.outer_rect,
+                    viewport.inner_rect,
+                    viewport.close_requested(),
+                )
+            });
+            if let (Some(outer), Some(inner)) = (outer, inner) {
+                self.mini_geometry = Some(WindowGeometry {
+                    x: outer.min.x,
+                    y: outer.min.y,
+                    width: inner.width(),
+                    height: inner.height(),
+                });
+            }
+
+            if close {
+                self.toggle_mini_window();
+            }
+        });
+    }
+
+    fn handle_keyboard(&mut self, ctx: &egui::Context) {
+        if let Some(action) = self.rebinding {
+            self.capture_binding(ctx, action);
+            return;
+        }
+
+        let overlay_open = self.palette.is_some() || self.show_shortcuts;
+        if self.presentation
+            && !overlay_open
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape))
+        {
+            self.set_presentation(ctx, false);
+        }
+
+        // Plain keys belong to a focused text field, shortcuts
+        // with modifiers keep working while typing. Adjusting time only
+        // makes sense with the timer in view, elsewhere the arrows scroll
+        let typing = ctx.wants_keyboard_input();
+        let timer_view = self.view == View::Countdown;
+        let pressed: Vec<Action> = self
+            .settings
+            .keybindings
+            .by_specificity()
+            .into_iter()
+            .filter(|(_, binding)| !typing || binding.ctrl || binding.alt)
+            .filter(|(action, _)| {
+                timer_view || !matches!(action, Action::AddTime | Action::RemoveTime)
+            })
+            .filter(|(_, binding)| {
+                ctx.input_mut(|i| i.consume_key(binding.modifiers(), binding.key))
+            })
+            .map(|(action, _)| action)
+            .collect();
+
+        for action in pressed {
+            self.run_action(ctx, action);
+        }
+    }
+
+    fn capture_binding(&mut self, ctx: &egui::Context, action: Action) {
+        let pressed = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some((*key, *modifiers)),
+                _ => None,
+            })
+        });
+
+        let Some((key, modifiers)) = pressed else {
+            return;
+        };
+
+        self.rebinding = None;
+        if key != egui::Key::Escape {
+            let binding = KeyBinding::from_input(key, modifiers);
+            self.settings.keybindings.set(action, Some(binding));
+            self.save_settings_keys(&["keybindings"]);
+        }
+    }
+
+    fn handle_shortcuts_signal(&mut self, signal: ShortcutsSignal) {
+        match signal {
+            ShortcutsSignal::StartRebind(action) => {
+                self.rebinding = Some(action);
+            }
+            ShortcutsSignal::Clear(action) => {
+                self.settings.keybindings.set(action, None);
+                self.save_settings_keys(&["keybindings"]);
+            }
+            ShortcutsSignal::ResetDefaults => {
+                self.settings.keybindings = KeyBindings::default();
+                self.save_settings_keys(&["keybindings"]);
+            }
+            ShortcutsSignal::Close => {
+                self.show_shortcuts = false;
+                self.rebinding = None;
+            }
+        }
+    }
+
+    fn handle_session_signal(&mut self, signal: SessionSignal) {
         match signal {
-            CountdownSignal::Finished => {
+            SessionSignal::JumpTo(index) => {
+                if let Some(signal) = self.countdown_element.jump_to(index) {
+                    self.handle_countdown_signal(signal);
+                }
+            }
+        }
+    }
+
+    fn draw_session_panel(&mut self, ctx: &egui::Context) {
+        egui::SidePanel::right("session_panel")
+            .resizable(true)
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                let overview = self.countdown_element.session_overview();
+                if let Some(signal) = self.session_panel.draw(ui, &overview) {
+                    self.handle_session_signal(signal);
+                }
+            });
+    }
+
+    fn handle_palette_signal(&mut self, ctx: &egui::Context, signal: PaletteSignal) {
+        self.palette = None;
+        match signal {
+            PaletteSignal::Run(action) => self.run_action(ctx, action),
+            PaletteSignal::LoadProfile(name) => {
+                self.load_profile(&name).unwrap_or_default();
+            }
+            PaletteSignal::Close => {}
+        }
+    }
+
+    fn record_interval(&mut self, summary: &IntervalSummary, completed: bool) {
+        let [r, g, b, _] = summary.color.to_array();
+        let record = Record {
+            finished_at: Utc::now().timestamp(),
+            planned_ms: summary.duration.as_millis() as u64,
+            elapsed_ms: summary.elapsed.as_millis() as u64,
+            color: (r, g, b),
+            completed,
+            laps_ms: summary
+                .laps
+                .iter()
+                .map(|lap| lap.as_millis() as u64)
+                .collect(),
+            label: summary.label.clone(),
+            adjusted_ms: summary.adjustment_ms,
+        };
+
+        // TODO: Handle error
+        let _ = self.history.append(record);
+    }
+
+    fn connect_notifier(&mut self) {
+        self.notifier = None;
+        self.notification_error = None;
+        if !self.settings.notifications {
+            return;
+        }
+
+        let notifier = match self.settings.notifications_bus.is_empty() {
+            true => Notifier::connect_session(),
+            false => Notifier::connect(&self.settings.notifications_bus),
+        };
+        self.notifier = Some(notifier);
+    }
+
+    // Only while the window is in the background, the app shows the rest itself
+    fn notify(&self, summary: String, body: String, actions: bool) {
+        if let Some(notifier) = &self.notifier
+            && !self.window_focused
+        {
+            notifier.notify(Notification {
+                summary,
+                body,
+                actions,
+            });
+        }
+    }
+
+    fn handle_notifications(&mut self) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+
+        let mut events = vec![];
+        while let Some(event) = notifier.poll_event() {
+            events.push(event);
+        }
+        for event in events {
+            match event {
+                NotifierEvent::Failed(e) => {
+                    self.notifier = None;
+                    self.notification_error = Some(format!("Notifications unavailable: {}", e));
+                    return;
+                }
+                NotifierEvent::Action(NotificationAction::Pause) => {
+                    if !self.countdown_element.is_paused() {
+                        self.countdown_element.toggle_running();
+                    }
+                }
+                NotifierEvent::Action(NotificationAction::Skip) => {
+                    if let Some(signal) = self.countdown_element.skip() {
+                        self.handle_countdown_signal(signal);
+                    }
+                }
+            }
+        }
+
+        let before = self.settings.notify_before_seconds;
+        let position = (
+            self.countdown_element.index(),
+            self.countdown_element.round(),
+        );
+        if before > 0
+            && self.notified_upcoming != Some(position)
+            && let Some(remaining) = self.countdown_element.remaining()
+            && remaining.as_secs() < before
+        {
+            self.notified_upcoming = Some(position);
+            let summary = format!("{} s left", remaining.as_secs() + 1);
+            self.notify(summary, self.countdown_element.next_up(), true);
+        }
+    }
+
+    fn start_mpris(&mut self) {
+        // Dropping the player closes its connection and frees the bus name
+        self.mpris = None;
+        self.mpris_error = None;
+        if !self.settings.mpris {
+            return;
+        }
+
+        let player = match self.settings.mpris_bus.is_empty() {
+            true => MprisPlayer::start_session(),
+            false => MprisPlayer::start(&self.settings.mpris_bus),
+        };
+        self.mpris = Some(player);
+    }
+
+    fn handle_mpris(&mut self, ctx: &egui::Context) {
+        let Some(player) = &self.mpris else {
+            return;
+        };
+
+        let state = match self.countdown_element.title_fields() {
+            Some(fields) => PlayerState {
+                has_timers: true,
+                playing: fields.running,
+                label: fields.label,
+                index: fields.index,
+                round: fields.round,
+                length: match fields.counts_up {
+                    true => None,
+                    false => Some(fields.elapsed + fields.remaining),
+                },
+                position: fields.elapsed,
+            },
+            None => PlayerState::default(),
+        };
+        player.update(state);
+
+        let mut commands = vec![];
+        while let Some(event) = player.poll_event() {
+            match event {
+                MprisEvent::Command(command) => commands.push(command),
+                MprisEvent::Failed(e) => {
+                    self.mpris = None;
+                    self.mpris_error = Some(format!("Media keys unavailable: {}", e));
+                    return;
+                }
+            }
+        }
+        for command in commands {
+            let paused = self.countdown_element.is_paused();
+            match command {
+                MprisCommand::PlayPause => self.run_action(ctx, Action::StartPause),
+                MprisCommand::Play if paused => self.run_action(ctx, Action::StartPause),
+                MprisCommand::Pause if !paused => self.run_action(ctx, Action::StartPause),
+                MprisCommand::Play | MprisCommand::Pause => {}
+                MprisCommand::Next => self.run_action(ctx, Action::Next),
+                MprisCommand::Previous => self.run_action(ctx, Action::Previous),
+            }
+        }
+    }
+
+    pub fn start_ipc(&mut self, ctx: &egui::Context) {
+        let Ok(path) = default_socket_path() else {
+            return;
+        };
+
+        let ctx = ctx.clone();
+        // TODO: Handle error, the app works without remote control
+        self.ipc = IpcServer::bind(&path, move || ctx.request_repaint()).ok();
+    }
+
+    fn status_json(&self) -> Json {
+        let mut status = vec![("profile", self.settings.profile.as_str().into())];
+        if let Some(fields) = self.countdown_element.title_fields() {
+            status.extend([
+                ("running", fields.running.into()),
+                ("ready", self.countdown_element.is_ready().into()),
+                ("label", fields.label.into()),
+                ("index", fields.index.into()),
+                ("count", fields.count.into()),
+                ("round", fields.round.into()),
+                ("counts_up", fields.counts_up.into()),
+                ("elapsed_ms", (fields.elapsed.as_millis() as u64).into()),
+                ("remaining_ms", (fields.remaining.as_millis() as u64).into()),
+            ]);
+        }
+        Json::object(status)
+    }
+
+    fn handle_ipc(&mut self, ctx: &egui::Context) {
+        let Some(server) = &self.ipc else {
+            return;
+        };
+
+        let mut requests = vec![];
+        while let Some(request) = server.poll() {
+            requests.push(request);
+        }
+
+        for (request, reply) in requests {
+            let paused = self.countdown_element.is_paused();
+            match request {
+                IpcRequest::Status => reply.ok(vec![("status", self.status_json())]),
+                IpcRequest::Start | IpcRequest::Pause => {
+                    if paused == (request == IpcRequest::Start) {
+                        self.run_action(ctx, Action::StartPause);
+                    }
+                    reply.ok(vec![]);
+                }
+                IpcRequest::Next => {
+                    self.run_action(ctx, Action::Next);
+                    reply.ok(vec![]);
+                }
+                IpcRequest::Prev => {
+                    self.run_action(ctx, Action::Previous);
+                    reply.ok(vec![]);
+                }
+                IpcRequest::LoadProfile(name) => match self.load_profile(&name) {
+                    Ok(()) => reply.ok(vec![]),
+                    Err(e) => reply.error(e),
+                },
+                IpcRequest::Args(args) => {
+                    let args = ["interval-timer".to_string()].into_iter().chain(args);
+                    match CliArgs::parse(args) {
+                        Ok(cli_args) => {
+                            self.apply_cli_args(ctx, cli_args);
+                            reply.ok(vec![]);
+                        }
+                        Err(e) => reply.error(e),
+                    }
+                }
+                // Answered by the server itself
+                IpcRequest::Subscribe => reply.ok(vec![]),
+            }
+        }
+
+        let state = (
+            !self.countdown_element.is_paused(),
+            self.countdown_element.index(),
+            self.countdown_element.round(),
+        );
+        if self.ipc_state != Some(state) {
+            self.ipc_state = Some(state);
+            self.broadcast("state", vec![("status", self.status_json())]);
+        }
+    }
+
+    // A second launch brings its arguments to this window instead
+    fn apply_cli_args(&mut self, ctx: &egui::Context, cli_args: CliArgs) {
+        if let Some((sequence, timers)) = cli_args.sequence {
+            self.script_timers = None;
+            self.settings.timers = timers;
+            self.settings.profile = String::new();
+            self.profile_style = None;
+            self.settings.sequence = sequence;
+            self.sequence_text = self.settings.sequence.clone();
+            self.sequence_error = None;
+            self.change_countdown_element();
+        }
+        if cli_args.kiosk {
+            self.set_presentation(ctx, true);
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+    }
+
+    fn broadcast(&self, event: &str, fields: Vec<(&str, Json)>) {
+        if let Some(server) = &self.ipc {
+            let mut entries = vec![("event", event.into())];
+            entries.extend(fields);
+            server.broadcast(Json::object(entries));
+        }
+    }
+
+    fn broadcast_interval(&self, event: &str, summary: &IntervalSummary) {
+        let fields = || {
+            vec![
+                ("label", summary.label.as_str().into()),
+                ("duration_ms", (summary.duration.as_millis() as u64).into()),
+                ("elapsed_ms", (summary.elapsed.as_millis() as u64).into()),
+            ]
+        };
+        self.broadcast(event, fields());
+
+        if let Some(mqtt) = &self.mqtt {
+            let mut entries = vec![("event", event.into())];
+            entries.extend(fields());
+            mqtt.publish("event", Json::object(entries).to_string(), false);
+        }
+    }
+
+    fn start_osc(&mut self) {
+        // The old socket has to go first, in case the port stays the same
+        self.osc = None;
+        self.osc_error = None;
+        self.osc_started = None;
+        if !self.settings.osc {
+            return;
+        }
+
+        let settings = &self.settings;
+        match Osc::start(
+            &settings.osc_target,
+            &settings.osc_bind,
+            settings.osc_listen_port,
+        ) {
+            Ok(osc) => self.osc = Some(osc),
+            Err(e) => self.osc_error = Some(e),
+        }
+    }
+
+    fn handle_osc(&mut self, ctx: &egui::Context) {
+        let Some(osc) = &self.osc else {
+            return;
+        };
+
+        let mut commands = vec![];
+        while let Some(command) = osc.poll_command() {
+            commands.push(command);
+        }
+        for command in commands {
+            let paused = self.countdown_element.is_paused();
+            match command {
+                OscCommand::Start if paused => self.run_action(ctx, Action::StartPause),
+                OscCommand::Pause if !paused => self.run_action(ctx, Action::StartPause),
+                OscCommand::Start | OscCommand::Pause => {}
+                OscCommand::Next => self.run_action(ctx, Action::Next),
+                OscCommand::Prev => self.run_action(ctx, Action::Previous),
+                OscCommand::Load(name) => {
+                    // Anyone on the network can send this, so say why it was refused
+                    if let Err(e) = self.load_profile(&name) {
+                        let message = format!("/timer/load {}: {}", name, e);
+                        if let Some(osc) = &self.osc {
+                            osc.send(&OscMessage::new(
+                                "/timer/error",
+                                vec![OscArg::Str(message.clone())],
+                            ));
+                        }
+                        self.osc_error = Some(message);
+                    }
+                }
+            }
+        }
+
+        let Some(osc) = &self.osc else {
+            return;
+        };
+        let Some(fields) = self.countdown_element.title_fields() else {
+            return;
+        };
+        if !fields.running {
+            return;
+        }
+
+        let position = (fields.index, fields.round);
+        if self.osc_started != Some(position) {
+            self.osc_started = Some(position);
+            let duration = match fields.counts_up {
+                true => Duration::ZERO,
+                false => fields.elapsed + fields.remaining,
+            };
+            let color = self.countdown_element.color();
+            osc.send(&interval_message(
+                "/timer/interval/start",
+                fields.index,
+                &fields.label,
+                color,
+                duration,
+            ));
+        }
+
+        let every = Duration::from_millis(self.settings.osc_remaining_ms);
+        if !every.is_zero() && self.osc_remaining_sent.elapsed() >= every {
+            self.osc_remaining_sent = Instant::now();
+            osc.send(&OscMessage::new(
+                "/timer/remaining",
+                vec![
+                    OscArg::Int(fields.remaining.as_millis() as i32),
+                    OscArg::Int(fields.index as i32),
+                    OscArg::Float(fields.fraction),
+                ],
+            ));
+        }
+    }
+
+    fn send_osc_finish(&self, summary: &IntervalSummary, missed: bool) {
+        let Some(osc) = &self.osc else {
+            return;
+        };
+        let mut message = interval_message(
+            "/timer/interval/finish",
+            summary.index,
+            &summary.label,
+            summary.color,
+            summary.duration,
+        );
+        message.args.push(OscArg::Int(missed as i32));
+        osc.send(&message);
+    }
+
+    fn start_mqtt(&mut self) {
+        // Dropping the client publishes offline and disconnects
+        self.mqtt = None;
+        self.mqtt_error = None;
+        self.mqtt_state = None;
+        self.mqtt_remaining = None;
+        if !self.settings.mqtt.enabled {
+            return;
+        }
+
+        match MqttClient::start(&self.settings.mqtt) {
+            Ok(client) => self.mqtt = Some(client),
+            Err(e) => self.mqtt_error = Some(e),
+        }
+    }
+
+    fn handle_mqtt(&mut self, ctx: &egui::Context) {
+        let Some(mqtt) = &self.mqtt else {
+            return;
+        };
+
+        let mut commands = vec![];
+        while let Some(command) = mqtt.poll_command() {
+            commands.push(command);
+        }
+        for command in commands {
+            let paused = self.countdown_element.is_paused();
+            match command {
+                MqttCommand::Start if paused => self.run_action(ctx, Action::StartPause),
+                MqttCommand::Pause if !paused => self.run_action(ctx, Action::StartPause),
+                MqttCommand::Start | MqttCommand::Pause => {}
+                MqttCommand::Next => self.run_action(ctx, Action::Next),
+                MqttCommand::Prev => self.run_action(ctx, Action::Previous),
+                MqttCommand::LoadProfile(name) => {
+                    // Only profiles that were saved here, whatever the broker sends
+                    let result = match load_profile_names().contains(&name) {
+                        true => self.load_profile(&name),
+                        false => Err(format!("No saved profile named '{}'", name)),
+                    };
+                    if let (Err(e), Some(mqtt)) = (result, &self.mqtt) {
+                        mqtt.publish("error", format!("cmd/load: {}", e), false);
+                    }
+                }
+            }
+        }
+
+        let Some(mqtt) = &self.mqtt else {
+            return;
+        };
+        let Some(fields) = self.countdown_element.title_fields() else {
+            if self.mqtt_state.take().is_some() {
+                mqtt.publish("state", "idle".to_string(), true);
+            }
+            return;
+        };
+
+        let state = (fields.running, fields.index, fields.round);
+        if self.mqtt_state != Some(state) {
+            let running = match (fields.running, self.countdown_element.is_ready()) {
+                (true, _) => "running",
+                (false, true) => "ready",
+                (false, false) => "paused",
+            };
+            mqtt.publish("state", running.to_string(), true);
+
+            if self.mqtt_state.map(|(_, index, round)| (index, round))
+                != Some((fields.index, fields.round))
+            {
+                let [r, g, b, _] = self.countdown_element.color().to_array();
+                let duration_ms = match fields.counts_up {
+                    true => Json::Null,
+                    false => ((fields.elapsed + fields.remaining).as_millis() as u64).into(),
+                };
+                let interval = Json::object(vec![
+                    ("label", fields.label.as_str().into()),
+                    ("index", fields.index.into()),
+                    ("count", fields.count.into()),
+                    ("round", fields.round.into()),
+                    ("color", format!("#{:02x}{:02x}{:02x}", r, g, b).into()),
+                    ("duration_ms", duration_ms),
+                ]);
+                mqtt.publish("interval", interval.to_string(), true);
+            }
+            self.mqtt_state = Some(state);
+        }
+
+        // Whole seconds, so subscribers get one message per second at most
+        let remaining = match fields.counts_up {
+            true => fields.elapsed.as_secs(),
+            false => fields.remaining.as_secs(),
+        };
+        if self.mqtt_remaining != Some(remaining) {
+            self.mqtt_remaining = Some(remaining);
+            mqtt.publish("remaining", remaining.to_string(), false);
+        }
+    }
+
+    fn webhook_fields(&self, summary: Option<&IntervalSummary>) -> WebhookFields {
+        let overview = self.countdown_element.session_overview();
+        let mut fields = WebhookFields {
+            profile: self.settings.profile.clone(),
+            count: overview.intervals.len(),
+            round: overview.round,
+            session_elapsed_ms: overview.elapsed.as_millis() as u64,
+            ..WebhookFields::default()
+        };
+
+        match summary {
+            Some(summary) => {
+                fields.index = summary.index;
+                fields.round = summary.round;
+                fields.label = summary.label.clone();
+                fields.duration_ms = summary.duration.as_millis() as u64;
+                fields.elapsed_ms = summary.elapsed.as_millis() as u64;
+            }
+            None => {
+                if let Some(current) = self.countdown_element.title_fields() {
+                    fields.index = current.index;
+                    fields.label = current.label;
+                    fields.duration_ms = (current.elapsed + current.remaining).as_millis() as u64;
+                    fields.elapsed_ms = current.elapsed.as_millis() as u64;
+                }
+            }
+        }
+        fields
+    }
+
+    // A session starts the first time the timer runs and ends with the sequence
+    fn handle_webhooks(&mut self) {
+        if !self.session_started && !self.countdown_element.is_paused() {
+            self.session_started = true;
+            let fields = self.webhook_fields(None);
+            self.webhooks.fire(WebhookEvent::SessionStart, &fields);
+        }
+    }
+
+    // Only once the timer has got somewhere, a session that never
+    // left the start has nothing to abandon
+    fn abandon_session(&mut self) {
+        if !self.session_started {
+            return;
+        }
+        self.session_started = false;
+
+        let countdown = &self.countdown_element;
+        if countdown.index() > 0 || countdown.round() > 0 || !countdown.elapsed().is_zero() {
+            let fields = self.webhook_fields(None);
+            self.webhooks.fire(WebhookEvent::SessionAbandoned, &fields);
+        }
+    }
+
+    fn handle_webhook_signal(&mut self, signal: WebhookSignal) -> Result<(), String> {
+        match signal {
+            WebhookSignal::Save(webhook) => self.webhooks.save(webhook)?,
+            WebhookSignal::Delete(name) => self.webhooks.delete(&name)?,
+            WebhookSignal::Test(name) => self.webhooks.test(&name),
+        }
+        Ok(())
+    }
+
+    // Scripts run once when loaded and may run their own timers from
+    // sequence(), leaving the saved timers and sequence alone
+    fn load_script(&mut self) {
+        self.script = None;
+        self.script_error = None;
+        self.script_started = None;
+        let generated = self.script_timers.take().is_some();
+        self.script = self.compile_script();
+        if generated || self.script_timers.is_some() {
+            self.change_countdown_element();
+        }
+    }
+
+    fn compile_script(&mut self) -> Option<Script> {
+        if self.settings.script.is_empty() {
+            return None;
+        }
+
+        let mut script = match Script::load(&self.settings.script) {
+            Ok(script) => script,
+            Err(e) => {
+                self.script_error = Some(e);
+                return None;
+            }
+        };
+
+        match script.generate() {
+            Ok(Some(timers)) if timers.is_empty() => {
+                self.script_error = Some("sequence() returned no intervals".to_string());
+            }
+            Ok(Some(timers)) => self.script_timers = Some(timers),
+            Ok(None) => {}
+            Err(e) => self.script_error = Some(e),
+        }
+        Some(script)
+    }
+
+    fn script_event(&self, summary: &IntervalSummary) -> ScriptEvent {
+        ScriptEvent {
+            index: summary.index,
+            round: summary.round,
+            label: summary.label.clone(),
+            duration: summary.duration,
+            elapsed: summary.elapsed,
+            intervals: self.countdown_element.intervals(),
+            missed: false,
+        }
+    }
+
+    fn handle_script(&mut self) {
+        if self.script.is_none() {
+            return;
+        }
+        let Some(fields) = self.countdown_element.title_fields() else {
+            return;
+        };
+
+        let position = (fields.index, fields.round);
+        if !fields.running || self.script_started == Some(position) {
+            return;
+        }
+        self.script_started = Some(position);
+
+        let Some(summary) = self.countdown_element.summary() else {
+            return;
+        };
+        let event = self.script_event(&summary);
+        if let Some(script) = &mut self.script {
+            let actions = script.on_start(&event);
+            self.apply_script_actions(actions);
+        }
+    }
+
+    fn run_script_finish(&mut self, summary: &IntervalSummary, missed: bool) {
+        let event = ScriptEvent {
+            missed,
+            ..self.script_event(summary)
+        };
+        if let Some(script) = &mut self.script {
+            let actions = script.on_finish(&event);
+            self.apply_script_actions(actions);
+        }
+    }
+
+    fn apply_script_actions(&mut self, actions: Result<Vec<ScriptAction>, String>) {
+        let actions = match actions {
+            Ok(actions) => actions,
+            Err(e) => {
+                self.script_error = Some(e);
+                return;
+            }
+        };
+
+        for action in actions {
+            match action {
+                ScriptAction::Skip => {
+                    // Unlike the skip button, keep going if the timer was running
+                    let running = !self.countdown_element.is_paused();
+                    if let Some(signal) = self.countdown_element.skip() {
+                        self.handle_countdown_signal(signal);
+                    }
+                    if running && self.countdown_element.is_paused() {
+                        self.countdown_element.toggle_running();
+                    }
+                }
+                ScriptAction::Extend(millis) => {
+                    self.countdown_element.adjust(millis, false);
+                }
+                ScriptAction::Insert(timer) => self.countdown_element.insert_next(&timer),
+                ScriptAction::Pause => {
+                    if !self.countdown_element.is_paused() {
+                        self.countdown_element.toggle_running();
+                    }
+                }
+            }
+        }
+    }
+
+    // Quick adjustments only last for this run of the interval, unless they
+    // are kept. Kept ones are written back to the timers and the profile
+    fn adjust_interval(&mut self, millis: i64) {
+        let index = self.countdown_element.index();
+        // Shuffled or script-made intervals, and the repeats of a sequence,
+        // do not line up with the timers one to one
+        let keep = self.settings.keep_adjustments
+            && self.settings.sequence.is_empty()
+            && self.script_timers.is_none()
+            && !self.settings.shuffle.is_active()
+            && self.countdown_element.intervals().len() == self.settings.timers.len();
+
+        let Some(duration) = self.countdown_element.adjust(millis, keep) else {
+            return;
+        };
+        if !keep {
+            return;
+        }
+
+        if let Some(timer) = self.settings.timers.get_mut(index) {
+            timer.millis = duration.as_millis() as u64;
+        }
+        self.sequence_text = timers_to_sequence(&self.settings.timers);
+
+        match self.settings.profile.is_empty() {
+            true => self.save_settings(),
+            false => {
+                // TODO: Handle error
+                let _ = self.save_profile(self.settings.profile.clone(), self.profile_style);
+            }
+        }
+    }
+
+    fn finish_interval(&mut self, summary: &IntervalSummary, missed: bool) {
+        self.send_osc_finish(summary, missed);
+        let fields = WebhookFields {
+            missed,
+            ..self.webhook_fields(Some(summary))
+        };
+        self.webhooks.fire(WebhookEvent::IntervalFinish, &fields);
+        if summary.completed_round && self.session_started {
+            self.session_started = false;
+            self.webhooks.fire(WebhookEvent::SessionComplete, &fields);
+        }
+        self.record_interval(summary, true);
+        self.run_script_finish(summary, missed);
+    }
+
+    pub fn handle_countdown_signal(&mut self, signal: CountdownSignal) {
+        match signal {
+            CountdownSignal::Finished(summary) => {
+                match summary.completed_round {
+                    true => self.notify(
+                        "Sequence complete".to_string(),
+                        format!("Round {} finished", summary.round + 1),
+                        !self.countdown_element.is_paused(),
+                    ),
+                    false if self.countdown_element.is_ready() => self.notify(
+                        format!("{} finished", interval_name(&summary)),
+                        "Press Start when ready".to_string(),
+                        false,
+                    ),
+                    false => self.notify(
+                        format!("{} finished", interval_name(&summary)),
+                        self.countdown_element.next_up(),
+                        true,
+                    ),
+                }
+
                 if self.settings.use_custom_sound && self.custom_sound.is_ready() {
                     self.custom_sound.play(self.settings.volume);
                 } else {
                     sound::play_sound(self.settings.volume);
                 }
+                self.broadcast_interval("finished", &summary);
+                self.finish_interval(&summary, false);
+            }
+            // Finished all the same, only without a chime or notification
+            CountdownSignal::Missed(summary) => {
+                self.broadcast_interval("missed", &summary);
+                self.finish_interval(&summary, true);
             }
+            CountdownSignal::Skipped(summary) => {
+                self.broadcast_interval("skipped", &summary);
+                self.record_interval(&summary, false);
+            }
+            CountdownSignal::Adjust(millis) => self.adjust_interval(millis),
         }
     }
 }
@@ -139,38 +1607,249 @@ impl App {
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         let repaint_clock = Clock::new(self.settings.tick_interval);
+        theme::apply(ctx, &self.settings);
+        self.window_focused = ctx.input(|i| i.focused);
+        self.handle_notifications();
+        self.handle_mpris(ctx);
+        self.handle_ipc(ctx);
+        self.handle_osc(ctx);
+        self.handle_mqtt(ctx);
+        self.handle_keyboard(ctx);
+        self.handle_webhooks();
+        self.handle_script();
+
+        // Before anything is drawn, so no view shows a stale interval
+        for signal in self.countdown_element.tick() {
+            self.handle_countdown_signal(signal);
+        }
+
+        if self.settings.session_panel && !self.presentation && self.view == View::Countdown {
+            self.draw_session_panel(ctx);
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            if self.presentation {
+                self.countdown_element.draw_presentation(ui);
+
+                if ctx.input(|i| i.pointer.time_since_last_movement() < 2.0) {
+                    let hint = egui::RichText::new("Press Esc to leave presentation mode").weak();
+                    ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                        ui.label(hint);
+                    });
+                }
+                return;
+            }
+
             ui.vertical_centered_justified(|ui| {
                 ui.spacing_mut().item_spacing.y = 8.0;
                 ui.horizontal(|ui| {
                     ui.heading("Interval Timer");
 
                     if ui.button("Settings").clicked() {
-                        self.toggle_settings();
+                        self.toggle_view(View::Settings);
+                    }
+
+                    if ui.button("Statistics").clicked() {
+                        self.toggle_view(View::Statistics);
+                    }
+
+                    if ui.button("Present").clicked() {
+                        self.set_presentation(ctx, true);
+                    }
+
+                    if ui
+                        .selectable_label(self.settings.session_panel, "Session")
+                        .clicked()
+                    {
+                        self.run_action(ctx, Action::SessionPanel);
+                    }
+
+                    if ui.selectable_label(self.mini_window, "Mini").clicked() {
+                        self.toggle_mini_window();
+                    }
+
+                    if self.mini_window {
+                        let mut click_through = self.settings.mini_click_through;
+                        let checkbox = ui
+                            .checkbox(&mut click_through, "Click-through")
+                            .on_hover_text("Clicks pass through the mini window");
+                        if checkbox.changed() {
+                            self.settings.mini_click_through = click_through;
+                            self.save_settings_keys(&["mini_click_through"]);
+                        }
+                    }
+
+                    if ui.button("?").on_hover_text("Keyboard shortcuts").clicked() {
+                        self.run_action(ctx, Action::Shortcuts);
                     }
                 });
                 ui.add_space(16.0);
 
-                if self.show_settings {
-                    if let Some(signal) = settings::draw(
-                        ui,
-                        &self.settings.timers,
-                        self.settings.play_once,
-                        self.settings.volume,
-                        self.settings.use_custom_sound,
-                        &self.settings.custom_sound_location,
-                    ) {
-                        self.handle_settings_signal(signal).unwrap_or_else(|_| {});
+                if self.view == View::Statistics {
+                    if let Some(statistics) = &self.statistics {
+                        statistics::draw(ui, statistics);
                     }
+                } else if self.view == View::Settings {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        let mqtt_error = self
+                            .mqtt_error
+                            .clone()
+                            .or_else(|| self.mqtt.as_ref().and_then(|mqtt| mqtt.error()));
+                        let errors = SettingsErrors {
+                            sequence: self.sequence_error.as_deref(),
+                            notifications: self.notification_error.as_deref(),
+                            mpris: self.mpris_error.as_deref(),
+                            osc: self.osc_error.as_deref(),
+                            mqtt: mqtt_error.as_deref(),
+                            script: self.script_error.as_deref(),
+                        };
+                        let script_output = self
+                            .script
+                            .as_ref()
+                            .map(|script| script.output())
+                            .unwrap_or_default();
+                        if let Some(signal) = settings::draw(
+                            ui,
+                            &self.settings,
+                            &self.sequence_text,
+                            &self.palette_name,
+                            &script_output,
+                            &errors,
+                        ) {
+                            self.handle_settings_signal(signal).unwrap_or_else(|_| {});
+                        }
+
+                        ui.add_space(16.0);
+                        let templates = self.template_library.all();
+                        if let Some(signal) =
+                            self.template_editor.draw(ui, &templates, &self.profiles)
+                        {
+                            self.handle_template_signal(signal).unwrap_or_default();
+                        }
+
+                        ui.add_space(16.0);
+                        let log = self.webhooks.log();
+                        if let Some(signal) = self.webhook_editor.draw(
+                            ui,
+                            self.webhooks.all(),
+                            &log,
+                            self.webhook_error.as_deref(),
+                        ) {
+                            self.webhook_error = self.handle_webhook_signal(signal).err();
+                        }
+                    });
                 } else {
-                    if let Some(signal) = self.countdown_element.draw(ui) {
+                    self.draw_resume_offer(ui);
+                    if let Some(error) = &self.script_error {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("Script: {}", error));
+                    }
+
+                    for signal in self.countdown_element.draw(ui) {
                         self.handle_countdown_signal(signal);
                     }
                 }
             });
         });
 
+        self.effects.draw(
+            ctx,
+            &self.settings.effects,
+            (
+                self.countdown_element.index(),
+                self.countdown_element.round(),
+            ),
+            self.countdown_element.color(),
+            self.countdown_element.remaining(),
+        );
+
+        let title_format = match self.settings.title_format.is_empty() {
+            true => DEFAULT_TITLE_FORMAT,
+            false => &self.settings.title_format,
+        };
+        self.window_title.update(
+            ctx,
+            title_format,
+            self.countdown_element.title_fields(),
+            self.countdown_element.color(),
+        );
+
+        if self.settings.debug_overlay {
+            self.draw_debug_overlay(ctx);
+        }
+
+        if self.mini_window {
+            self.draw_mini_window(ctx);
+        }
+
+        if self.show_shortcuts
+            && let Some(signal) = shortcuts::draw(ctx, &self.settings.keybindings, self.rebinding)
+        {
+            self.handle_shortcuts_signal(signal);
+        }
+
+        if let Some(palette) = &mut self.palette
+            && let Some(signal) = palette.draw(ctx, &self.settings.keybindings, &self.profiles)
+        {
+            self.handle_palette_signal(ctx, signal);
+        }
+
+        // Keep the pending session untouched until the
+        // user has decided whether to resume it
+        if self.resume_offer.is_none() && self.session_save_clock.get_state() == State::Finished {
+            self.save_session();
+            self.session_save_clock.reset();
+        }
+
         ctx.request_repaint_after(repaint_clock.remaining());
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.abandon_session();
+        self.webhooks.flush(WEBHOOK_FLUSH_TIMEOUT);
+    }
+}
+
+fn load_profile_names() -> Vec<String> {
+    // TODO: Handle error
+    default_profiles_dir()
+        .ok()
+        .and_then(|dir| list_profiles(&dir).ok())
+        .unwrap_or_default()
+}
+
+fn interval_name(summary: &IntervalSummary) -> String {
+    match summary.label.is_empty() {
+        true => "Interval".to_string(),
+        false => summary.label.clone(),
+    }
+}
+
+fn interval_message(
+    address: &str,
+    index: usize,
+    label: &str,
+    color: Color32,
+    duration: Duration,
+) -> OscMessage {
+    OscMessage::new(
+        address,
+        vec![
+            OscArg::Int(index as i32),
+            OscArg::Str(label.to_string()),
+            OscArg::Int(color.r() as i32),
+            OscArg::Int(color.g() as i32),
+            OscArg::Int(color.b() as i32),
+            OscArg::Int(duration.as_millis() as i32),
+        ],
+    )
+}
+
+fn settings_path() -> String {
+    default_config_path().unwrap_or(
+        // TODO: Handle this error better
+        env::temp_dir()
+            .join("config.txt")
+            .to_string_lossy()
+            .to_string(),
+    )
 }
@@ -0,0 +1,54 @@
+use std::env;
+
+use interval_timer::internal::dirs::default_socket_path;
+use interval_timer::internal::ipc::{IpcRequest, send_request, subscribe};
+
+const USAGE: &str = "Usage: interval-timerctl <command>
+
+Controls a running interval-timer.
+
+Commands:
+  status               Print the current interval as JSON
+  start                Start or resume the timer
+  pause                Pause the timer
+  next                 Skip to the next interval
+  prev                 Go back to the previous interval
+  load-profile <name>  Load a saved profile
+  subscribe            Print events as JSON lines until the app exits";
+
+fn main() -> Result<(), String> {
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+
+    let request = match command.as_str() {
+        "status" => IpcRequest::Status,
+        "start" => IpcRequest::Start,
+        "pause" => IpcRequest::Pause,
+        "next" => IpcRequest::Next,
+        "prev" => IpcRequest::Prev,
+        "subscribe" => IpcRequest::Subscribe,
+        "load-profile" => {
+            let name = args.next().ok_or("Missing profile name, see --help")?;
+            IpcRequest::LoadProfile(name)
+        }
+        "" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        _ => return Err(format!("Unknown command '{}', see --help", command)),
+    };
+
+    let socket = default_socket_path()?;
+    if request == IpcRequest::Subscribe {
+        return subscribe(&socket, |event| println!("{}", event));
+    }
+
+    let reply = send_request(&socket, &request)?;
+    if let Some(error) = reply.get("error").and_then(|v| v.as_str()) {
+        return Err(error.to_string());
+    }
+    if let Some(status) = reply.get("status") {
+        println!("{}", status);
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use crate::internal::sequence::{expand, parse_sequence};
+use crate::internal::timer::Timer;
+
+pub const USAGE: &str = "Usage: interval-timer [--kiosk] [--sequence <text>]
+
+Options:
+  -k, --kiosk            Start fullscreen in presentation mode
+  -s, --sequence <text>  Run the given sequence instead of the configured timers,
+                         e.g. \"warmup 5m blue; 8x(work 20s red, rest 10s green)\"
+  -h, --help             Show this message";
+
+#[derive(Default, Debug)]
+pub struct CliArgs {
+    pub help: bool,
+    pub kiosk: bool,
+    pub sequence: Option<(String, Vec<Timer>)>,
+}
+
+impl CliArgs {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut cli_args = CliArgs::default();
+        let mut args = args.skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => cli_args.help = true,
+                "-k" | "--kiosk" => cli_args.kiosk = true,
+                "-s" | "--sequence" => {
+                    let text = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for '{}'", arg))?;
+                    let items =
+                        parse_sequence(&text).map_err(|e| format!("Invalid sequence: {}", e))?;
+                    cli_args.sequence = Some((text, expand(&items)));
+                }
+                _ => return Err(format!("Unknown argument '{}', see --help", arg)),
+            }
+        }
+
+        Ok(cli_args)
+    }
+}
@@ -8,31 +8,88 @@ pub enum State {
     Finished,
 }
 
+// Where a clock reads the time from, tests pass a simulated one
+pub type Now = fn() -> Instant;
+
 #[derive(Debug)]
 pub struct Clock {
     start: Instant,
     duration: Duration,
     running: bool,
+    paused_elapsed: Duration,
+    count_up: bool,
+    now: Now,
 }
 
+// Count-up clocks have no end, so their progress
+// sweeps around once per lap of this length
+const COUNT_UP_LAP: Duration = Duration::from_secs(60);
+
 impl Clock {
     pub fn new(millis: u64) -> Self {
+        Self::new_with(millis, Instant::now)
+    }
+
+    pub fn new_count_up() -> Self {
+        Self::new_count_up_with(Instant::now)
+    }
+
+    pub fn new_with(millis: u64, now: Now) -> Self {
         Self {
-            start: Instant::now(),
+            start: now(),
             duration: Duration::from_millis(millis),
             running: true,
+            paused_elapsed: Duration::ZERO,
+            count_up: false,
+            now,
         }
     }
 
+    pub fn new_count_up_with(now: Now) -> Self {
+        Self {
+            count_up: true,
+            ..Self::new_with(0, now)
+        }
+    }
+
+    pub fn counts_up(&self) -> bool {
+        self.count_up
+    }
+
     pub fn elapsed(&self) -> Duration {
+        self.elapsed_at((self.now)())
+    }
+
+    pub fn elapsed_at(&self, now: Instant) -> Duration {
         if !self.running {
-            return Duration::ZERO;
+            return self.paused_elapsed;
+        }
+
+        now.saturating_duration_since(self.start)
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.running {
+            true => Some(self.start + self.duration),
+            false => None,
         }
+    }
 
-        Instant::now() - self.start
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    // Keeps the elapsed time, so the remaining time moves by the difference
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
     }
 
     pub fn fraction(&self) -> f32 {
+        if self.count_up {
+            let lap = self.elapsed().as_secs_f32() % COUNT_UP_LAP.as_secs_f32();
+            return lap / COUNT_UP_LAP.as_secs_f32();
+        }
+
         match self.duration {
             Duration::ZERO => 1.0,
             _ => self.elapsed().div_duration_f32(self.duration),
@@ -41,10 +98,6 @@ impl Clock {
     }
 
     pub fn remaining(&self) -> Duration {
-        if !self.running {
-            return self.duration;
-        }
-
         let elapsed = self.elapsed();
 
         match elapsed >= self.duration {
@@ -54,23 +107,47 @@ impl Clock {
     }
 
     pub fn reset(&mut self) {
-        self.start = Instant::now();
+        self.reset_at((self.now)());
+    }
+
+    pub fn reset_at(&mut self, start: Instant) {
+        self.start = start;
+        self.paused_elapsed = Duration::ZERO;
     }
 
     pub fn start(&mut self) {
-        self.running = true;
-        self.reset();
+        if !self.running {
+            self.start = started_before((self.now)(), self.paused_elapsed);
+            self.running = true;
+        }
     }
 
     pub fn stop(&mut self) {
-        self.running = false;
+        if self.running {
+            self.paused_elapsed = self.elapsed();
+            self.running = false;
+        }
+    }
+
+    pub fn restore(&mut self, elapsed: Duration, running: bool) {
+        self.start = started_before((self.now)(), elapsed);
+        self.paused_elapsed = elapsed;
+        self.running = running;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
     }
 
     pub fn get_state(&self) -> State {
+        self.get_state_at((self.now)())
+    }
+
+    pub fn get_state_at(&self, now: Instant) -> State {
         match self.running {
             false => State::Stopped,
             true => {
-                if self.elapsed() >= self.duration {
+                if !self.count_up && self.elapsed_at(now) >= self.duration {
                     State::Finished
                 } else {
                     State::Running
@@ -82,14 +159,30 @@ impl Clock {
 
 impl fmt::Display for Clock {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let secs = self.remaining().as_secs();
-        let seconds = self.remaining().as_secs_f32() % 60.0;
+        let (sign, shown) = match self.count_up {
+            true => ("+", self.elapsed()),
+            false => ("", self.remaining()),
+        };
+
+        let secs = shown.as_secs();
+        let seconds = shown.as_secs_f32() % 60.0;
         let minutes = (secs / 60) % 60;
         let hours = (secs / 60) / 60;
-        write!(f, "{}:{:0>2}:{}", hours, minutes, format_secs(seconds))
+        write!(
+            f,
+            "{}{}:{:0>2}:{}",
+            sign,
+            hours,
+            minutes,
+            format_secs(seconds)
+        )
     }
 }
 
+fn started_before(now: Instant, elapsed: Duration) -> Instant {
+    now.checked_sub(elapsed).unwrap_or(now)
+}
+
 fn format_secs(val: f32) -> String {
     let whole = val.trunc() as u32;
     let frac = ((val.fract() * 100.0).round()) as u32;
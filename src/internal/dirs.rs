@@ -1,6 +1,6 @@
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn config_dir() -> Result<PathBuf, &'static str> {
     let os_config_dir = match env::consts::OS {
@@ -32,14 +32,133 @@ fn config_dir() -> Result<PathBuf, &'static str> {
     Ok(os_config_dir.join("interval-timer"))
 }
 
+fn data_dir() -> Result<PathBuf, &'static str> {
+    let os_data_dir = match env::consts::OS {
+        "linux" => {
+            if let Some(xdg) = env::var_os("XDG_DATA_HOME") {
+                Ok(PathBuf::from(xdg))
+            } else if let Some(home) = env::var_os("HOME") {
+                Ok(PathBuf::from(home).join(".local").join("share"))
+            } else {
+                Err("env var $HOME is not set.")
+            }
+        }
+
+        "windows" => match env::var_os("LOCALAPPDATA") {
+            Some(appdata) => Ok(PathBuf::from(appdata)),
+            None => Err("env var %LOCALAPPDATA% is not set."),
+        },
+
+        "macos" => match env::var_os("HOME") {
+            Some(home) => Ok(PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")),
+            None => Err("env var $HOME is not set."),
+        },
+
+        _ => Err("OS not supported"),
+    }?;
+
+    Ok(os_data_dir.join("interval-timer"))
+}
+
 pub fn default_config_path() -> Result<String, &'static str> {
     let dir = config_dir()?;
     let path = dir.join("config.txt");
     Ok(path.to_string_lossy().to_string())
 }
 
+pub fn default_profiles_dir() -> Result<String, &'static str> {
+    let dir = config_dir()?;
+    let path = dir.join("profiles");
+    Ok(path.to_string_lossy().to_string())
+}
+
+pub fn default_templates_path() -> Result<String, &'static str> {
+    let dir = config_dir()?;
+    let path = dir.join("templates.txt");
+    Ok(path.to_string_lossy().to_string())
+}
+
+pub fn default_webhooks_path() -> Result<String, &'static str> {
+    let dir = config_dir()?;
+    let path = dir.join("webhooks.txt");
+    Ok(path.to_string_lossy().to_string())
+}
+
+pub fn default_history_path() -> Result<String, &'static str> {
+    let dir = data_dir()?;
+    let path = dir.join("history.txt");
+    Ok(path.to_string_lossy().to_string())
+}
+
+pub fn default_session_path() -> Result<String, &'static str> {
+    let dir = data_dir()?;
+    let path = dir.join("session.txt");
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Only in a per-user runtime directory, never a shared one like /tmp
+// where another user could put their own socket in its place
+pub fn default_socket_path() -> Result<String, &'static str> {
+    let dir = match env::consts::OS {
+        "linux" => match env::var_os("XDG_RUNTIME_DIR") {
+            Some(runtime) => PathBuf::from(runtime),
+            None => return Err("env var $XDG_RUNTIME_DIR is not set."),
+        },
+        // $TMPDIR is per user on macOS
+        "macos" => env::temp_dir(),
+        _ => return Err("OS not supported"),
+    };
+
+    if !is_private(&dir) {
+        return Err("Runtime directory is writable by other users.");
+    }
+    Ok(dir
+        .join("interval-timer.sock")
+        .to_string_lossy()
+        .to_string())
+}
+
+#[cfg(unix)]
+fn is_private(dir: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(dir)
+        .is_ok_and(|metadata| metadata.is_dir() && metadata.permissions().mode() & 0o022 == 0)
+}
+
+#[cfg(not(unix))]
+fn is_private(_dir: &Path) -> bool {
+    false
+}
+
 pub fn create_dirs_if_not_exists() -> Result<(), &'static str> {
     let dir = config_dir()?;
+    fs::create_dir_all(dir.join("profiles")).ok();
+    let dir = data_dir()?;
     fs::create_dir_all(dir).ok();
     Ok(())
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+    use std::process;
+
+    use super::*;
+
+    #[test]
+    fn only_private_dirs_hold_the_socket() {
+        let dir = env::temp_dir().join(format!("interval-timer-runtime-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).unwrap();
+        assert!(is_private(&dir));
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o1777)).unwrap();
+        assert!(!is_private(&dir));
+        assert!(!is_private(&dir.join("missing")));
+
+        fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+// Scores how well the query matches the candidate as a case-insensitive
+// subsequence, favouring consecutive letters and the starts of words
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for c in query {
+        let position = candidate[next..].iter().position(|v| *v == c)? + next;
+
+        score += 1;
+        if previous_match.is_some_and(|v| v + 1 == position) {
+            score += 5;
+        }
+        if position == 0 || !candidate[position - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (position - next).min(5) as i32;
+
+        previous_match = Some(position);
+        next = position + 1;
+    }
+
+    Some(score)
+}
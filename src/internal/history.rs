@@ -0,0 +1,140 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+use crate::internal::settings_parser::parse_color;
+
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub finished_at: i64,
+    pub planned_ms: u64,
+    pub elapsed_ms: u64,
+    pub color: (u8, u8, u8),
+    pub completed: bool,
+    pub laps_ms: Vec<u64>,
+    pub label: String,
+    // Time added, or taken off when negative, while the interval ran
+    pub adjusted_ms: i64,
+}
+
+pub struct History {
+    path: String,
+    records: Vec<Record>,
+}
+
+impl History {
+    pub fn new_from_file(path: &str) -> Result<Self, String> {
+        let file_content = match fs::read_to_string(path) {
+            Ok(value) => value,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Unhandled io error: {}", e)),
+        };
+
+        // A line cut short by a crash should not cost the user
+        // the rest of their history, so bad lines are skipped
+        let records = file_content
+            .lines()
+            .filter_map(|line| parse_record(line).ok())
+            .collect();
+
+        Ok(Self {
+            path: path.to_string(),
+            records,
+        })
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    pub fn append(&mut self, record: Record) -> Result<(), String> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|v| v.to_string())?;
+
+        writeln!(file, "{}", record_to_string(&record)).map_err(|v| v.to_string())?;
+        self.records.push(record);
+        Ok(())
+    }
+}
+
+fn parse_record(line: &str) -> Result<Record, String> {
+    let fields: Vec<&str> = line.split(',').collect();
+    if !(5..=8).contains(&fields.len()) {
+        return Err(format!("Expected 5 to 8 fields in history line '{}'", line));
+    }
+
+    let finished_at = fields[0]
+        .parse::<i64>()
+        .map_err(|e| format!("Invalid timestamp '{}': {}", fields[0], e))?;
+    let planned_ms = fields[1]
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid planned time '{}': {}", fields[1], e))?;
+    let elapsed_ms = fields[2]
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid elapsed time '{}': {}", fields[2], e))?;
+    let color = parse_color(fields[3])?;
+    let completed = fields[4]
+        .parse::<bool>()
+        .map_err(|e| format!("Invalid completed flag '{}': {}", fields[4], e))?;
+    let laps_ms = match fields.get(5) {
+        Some(laps) => laps
+            .split(';')
+            .filter(|lap| !lap.is_empty())
+            .map(|lap| {
+                lap.parse::<u64>()
+                    .map_err(|e| format!("Invalid lap '{}': {}", lap, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+    let label = fields.get(6).unwrap_or(&"").to_string();
+    let adjusted_ms = match fields.get(7) {
+        Some(adjusted) => adjusted
+            .parse::<i64>()
+            .map_err(|e| format!("Invalid adjustment '{}': {}", adjusted, e))?,
+        None => 0,
+    };
+
+    Ok(Record {
+        finished_at,
+        planned_ms,
+        elapsed_ms,
+        color,
+        completed,
+        laps_ms,
+        label,
+        adjusted_ms,
+    })
+}
+
+fn record_to_string(record: &Record) -> String {
+    let line = format!(
+        "{},{},{},{:02x}{:02x}{:02x},{}",
+        record.finished_at,
+        record.planned_ms,
+        record.elapsed_ms,
+        record.color.0,
+        record.color.1,
+        record.color.2,
+        record.completed
+    );
+
+    if record.laps_ms.is_empty() && record.label.is_empty() && record.adjusted_ms == 0 {
+        return line;
+    }
+
+    let laps = record
+        .laps_ms
+        .iter()
+        .map(|lap| lap.to_string())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    match (record.label.is_empty(), record.adjusted_ms) {
+        (true, 0) => format!("{},{}", line, laps),
+        (_, 0) => format!("{},{},{}", line, laps, record.label),
+        (_, adjusted) => format!("{},{},{},{}", line, laps, record.label, adjusted),
+    }
+}
@@ -0,0 +1,339 @@
+use std::sync::mpsc::{Receiver, Sender};
+
+use crate::internal::json::{Json, parse_json};
+
+// One JSON object per line in both directions, e.g.
+// {"cmd":"load-profile","name":"Legs"} answered by {"ok":true}
+#[derive(Clone, Debug, PartialEq)]
+pub enum IpcRequest {
+    Status,
+    Start,
+    Pause,
+    Next,
+    Prev,
+    LoadProfile(String),
+    Subscribe,
+    // Command line of a second launch, forwarded to the running instance
+    Args(Vec<String>),
+}
+
+pub struct IpcReply(Sender<Json>);
+
+// Listens on a Unix socket, requests are answered by the app
+// through poll, events go out to every subscribed client
+pub struct IpcServer {
+    requests: Receiver<(IpcRequest, IpcReply)>,
+    events: Sender<Json>,
+    path: String,
+}
+
+impl IpcRequest {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let json = parse_json(line)?;
+        let cmd = json
+            .get("cmd")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing \"cmd\"")?;
+
+        match cmd {
+            "status" => Ok(IpcRequest::Status),
+            "start" => Ok(IpcRequest::Start),
+            "pause" => Ok(IpcRequest::Pause),
+            "next" => Ok(IpcRequest::Next),
+            "prev" => Ok(IpcRequest::Prev),
+            "subscribe" => Ok(IpcRequest::Subscribe),
+            "load-profile" => json
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map(|v| IpcRequest::LoadProfile(v.to_string()))
+                .ok_or_else(|| "Missing \"name\"".to_string()),
+            "args" => json
+                .get("args")
+                .and_then(|v| v.as_array())
+                .and_then(|args| {
+                    args.iter()
+                        .map(|v| v.as_str().map(|v| v.to_string()))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .map(IpcRequest::Args)
+                .ok_or_else(|| "Expected \"args\" to be a list of strings".to_string()),
+            _ => Err(format!("Unknown command '{}'", cmd)),
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let cmd = |name: &str| ("cmd", Json::from(name));
+        match self {
+            IpcRequest::Status => Json::object(vec![cmd("status")]),
+            IpcRequest::Start => Json::object(vec![cmd("start")]),
+            IpcRequest::Pause => Json::object(vec![cmd("pause")]),
+            IpcRequest::Next => Json::object(vec![cmd("next")]),
+            IpcRequest::Prev => Json::object(vec![cmd("prev")]),
+            IpcRequest::Subscribe => Json::object(vec![cmd("subscribe")]),
+            IpcRequest::LoadProfile(name) => {
+                Json::object(vec![cmd("load-profile"), ("name", name.as_str().into())])
+            }
+            IpcRequest::Args(args) => Json::object(vec![
+                cmd("args"),
+                (
+                    "args",
+                    Json::Array(args.iter().map(|v| v.as_str().into()).collect()),
+                ),
+            ]),
+        }
+    }
+}
+
+impl IpcReply {
+    pub fn ok(self, fields: Vec<(&str, Json)>) {
+        let mut entries = vec![("ok", Json::Bool(true))];
+        entries.extend(fields);
+        let _ = self.0.send(Json::object(entries));
+    }
+
+    pub fn error(self, error: String) {
+        let _ = self.0.send(error_reply(error));
+    }
+}
+
+impl IpcServer {
+    // Wake is called for every request, so a hidden window still answers
+    pub fn bind(path: &str, wake: impl Fn() + Send + Sync + 'static) -> Result<Self, String> {
+        socket::bind(path, wake)
+    }
+
+    pub fn poll(&self) -> Option<(IpcRequest, IpcReply)> {
+        self.requests.try_recv().ok()
+    }
+
+    pub fn broadcast(&self, event: Json) {
+        let _ = self.events.send(event);
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// Sends one request and waits for its reply
+pub fn send_request(path: &str, request: &IpcRequest) -> Result<Json, String> {
+    socket::send_request(path, request)
+}
+
+// Keeps reading events after a subscribe, until the app goes away
+pub fn subscribe(path: &str, on_event: impl FnMut(Json)) -> Result<(), String> {
+    socket::subscribe(path, on_event)
+}
+
+fn error_reply(error: String) -> Json {
+    Json::object(vec![("ok", Json::Bool(false)), ("error", error.into())])
+}
+
+#[cfg(unix)]
+mod socket {
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::mpsc::{self, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{IpcReply, IpcRequest, IpcServer, error_reply};
+    use crate::internal::json::{Json, parse_json};
+
+    // Longer than any frame, short enough for scripts not to hang
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+    // The app gives up on itself after REPLY_TIMEOUT, this covers a hung app
+    const CLIENT_TIMEOUT: Duration = Duration::from_secs(6);
+
+    type Subscribers = Arc<Mutex<Vec<UnixStream>>>;
+    type Requests = Sender<(IpcRequest, IpcReply)>;
+
+    pub fn bind(path: &str, wake: impl Fn() + Send + Sync + 'static) -> Result<IpcServer, String> {
+        // A socket nobody answers on is left over from a crash
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!("Another instance is listening on {}", path));
+        }
+        let _ = std::fs::remove_file(path);
+        let listener = UnixListener::bind(path).map_err(|e| e.to_string())?;
+
+        let (requests, receiver) = mpsc::channel();
+        let (events, event_receiver) = mpsc::channel::<Json>();
+        let subscribers: Subscribers = Arc::new(Mutex::new(vec![]));
+        let wake = Arc::new(wake);
+
+        let shared = subscribers.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let requests = requests.clone();
+                let subscribers = shared.clone();
+                let wake = wake.clone();
+                thread::spawn(move || serve_client(stream, requests, subscribers, &*wake));
+            }
+        });
+
+        thread::spawn(move || {
+            for event in event_receiver {
+                let line = format!("{}\n", event);
+                if let Ok(mut subscribers) = subscribers.lock() {
+                    subscribers.retain_mut(|stream| stream.write_all(line.as_bytes()).is_ok());
+                }
+            }
+        });
+
+        Ok(IpcServer {
+            requests: receiver,
+            events,
+            path: path.to_string(),
+        })
+    }
+
+    fn serve_client(
+        stream: UnixStream,
+        requests: Requests,
+        subscribers: Subscribers,
+        wake: &dyn Fn(),
+    ) {
+        let Ok(mut writer) = stream.try_clone() else {
+            return;
+        };
+
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = match IpcRequest::parse(&line) {
+                Ok(IpcRequest::Subscribe) => {
+                    // Answered before joining, so no event can overtake the reply
+                    let reply = Json::object(vec![("ok", Json::Bool(true))]);
+                    let Ok(subscriber) = writer.try_clone() else {
+                        return;
+                    };
+                    if writeln!(writer, "{}", reply).is_err() {
+                        return;
+                    }
+                    // Slow readers are dropped rather than holding up everyone else
+                    let _ = subscriber.set_write_timeout(Some(REPLY_TIMEOUT));
+                    if let Ok(mut subscribers) = subscribers.lock() {
+                        subscribers.push(subscriber);
+                    }
+                    continue;
+                }
+                Ok(request) => {
+                    let (sender, receiver) = mpsc::channel();
+                    if requests.send((request, IpcReply(sender))).is_err() {
+                        return;
+                    }
+                    wake();
+                    receiver
+                        .recv_timeout(REPLY_TIMEOUT)
+                        .unwrap_or_else(|_| error_reply("The app did not answer".to_string()))
+                }
+                Err(e) => error_reply(e),
+            };
+
+            if writeln!(writer, "{}", reply).is_err() {
+                return;
+            }
+        }
+    }
+
+    fn connect(path: &str, request: &IpcRequest) -> Result<BufReader<UnixStream>, String> {
+        let mut stream = UnixStream::connect(path)
+            .map_err(|e| format!("Could not connect to {}: {}", path, e))?;
+        stream
+            .set_read_timeout(Some(CLIENT_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        stream
+            .set_write_timeout(Some(CLIENT_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+        writeln!(stream, "{}", request.to_json()).map_err(|e| e.to_string())?;
+        Ok(BufReader::new(stream))
+    }
+
+    fn read_reply(reader: &mut BufReader<UnixStream>) -> Result<Json, String> {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).map_err(|e| match e.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+                "The app did not answer".to_string()
+            }
+            _ => e.to_string(),
+        })?;
+        match read {
+            0 => Err("The app closed the connection".to_string()),
+            _ => parse_json(&line),
+        }
+    }
+
+    pub fn send_request(path: &str, request: &IpcRequest) -> Result<Json, String> {
+        let mut reader = connect(path, request)?;
+        read_reply(&mut reader)
+    }
+
+    pub fn subscribe(path: &str, mut on_event: impl FnMut(Json)) -> Result<(), String> {
+        let mut reader = connect(path, &IpcRequest::Subscribe)?;
+        read_reply(&mut reader)?;
+        // Events may be minutes apart
+        reader
+            .get_ref()
+            .set_read_timeout(None)
+            .map_err(|e| e.to_string())?;
+        loop {
+            on_event(read_reply(&mut reader)?);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod socket {
+    use super::{IpcRequest, IpcServer};
+    use crate::internal::json::Json;
+
+    const UNSUPPORTED: &str = "Remote control needs Unix domain sockets";
+
+    pub fn bind(
+        _path: &str,
+        _wake: impl Fn() + Send + Sync + 'static,
+    ) -> Result<IpcServer, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn send_request(_path: &str, _request: &IpcRequest) -> Result<Json, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn subscribe(_path: &str, _on_event: impl FnMut(Json)) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::net::UnixListener;
+    use std::process;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[test]
+    fn gives_up_on_an_app_that_never_answers() {
+        let path = std::env::temp_dir().join(format!("interval-timer-test-{}.sock", process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let started = Instant::now();
+        let reply = send_request(&path, &IpcRequest::Status);
+        assert_eq!(reply.unwrap_err(), "The app did not answer");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        drop(listener);
+        let _ = std::fs::remove_file(&path);
+    }
+}
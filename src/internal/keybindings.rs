@@ -0,0 +1,286 @@
+use eframe::egui::{Key, Modifiers};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    StartPause,
+    Previous,
+    Next,
+    Restart,
+    Settings,
+    Statistics,
+    Save,
+    Fullscreen,
+    Presentation,
+    MiniWindow,
+    SessionPanel,
+    Shortcuts,
+    CommandPalette,
+    AddTime,
+    RemoveTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: Vec<(Action, Option<KeyBinding>)>,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::StartPause,
+        Action::Previous,
+        Action::Next,
+        Action::Restart,
+        Action::Settings,
+        Action::Statistics,
+        Action::Save,
+        Action::Fullscreen,
+        Action::Presentation,
+        Action::MiniWindow,
+        Action::SessionPanel,
+        Action::Shortcuts,
+        Action::CommandPalette,
+        Action::AddTime,
+        Action::RemoveTime,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::StartPause => "Start / pause",
+            Action::Previous => "Previous interval",
+            Action::Next => "Next interval",
+            Action::Restart => "Restart interval",
+            Action::Settings => "Open settings",
+            Action::Statistics => "Open statistics",
+            Action::Save => "Save settings",
+            Action::Fullscreen => "Toggle fullscreen",
+            Action::Presentation => "Presentation mode",
+            Action::MiniWindow => "Mini window",
+            Action::SessionPanel => "Session overview",
+            Action::Shortcuts => "Show shortcuts",
+            Action::CommandPalette => "Command palette",
+            Action::AddTime => "Add time to the interval",
+            Action::RemoveTime => "Take time off the interval",
+        }
+    }
+
+    fn default_binding(&self) -> Option<KeyBinding> {
+        let binding = match self {
+            Action::StartPause => KeyBinding::new(Key::Space),
+            Action::Previous => KeyBinding::new(Key::ArrowLeft),
+            Action::Next => KeyBinding::new(Key::ArrowRight),
+            Action::Restart => KeyBinding::new(Key::R),
+            Action::Settings => KeyBinding::new(Key::S),
+            Action::Statistics => return None,
+            Action::Save => KeyBinding::ctrl(Key::S),
+            Action::Fullscreen => KeyBinding::new(Key::F11),
+            Action::Presentation => KeyBinding::new(Key::F5),
+            Action::MiniWindow => KeyBinding::ctrl(Key::M),
+            Action::SessionPanel => KeyBinding::new(Key::L),
+            Action::Shortcuts => KeyBinding::new(Key::F1),
+            Action::CommandPalette => KeyBinding::ctrl(Key::K),
+            Action::AddTime => KeyBinding::new(Key::ArrowUp),
+            Action::RemoveTime => KeyBinding::new(Key::ArrowDown),
+        };
+        Some(binding)
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.to_string() == value)
+            .ok_or_else(|| format!("Unknown action '{}'", value))
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::StartPause => write!(f, "start_pause"),
+            Action::Previous => write!(f, "previous"),
+            Action::Next => write!(f, "next"),
+            Action::Restart => write!(f, "restart"),
+            Action::Settings => write!(f, "settings"),
+            Action::Statistics => write!(f, "statistics"),
+            Action::Save => write!(f, "save"),
+            Action::Fullscreen => write!(f, "fullscreen"),
+            Action::Presentation => write!(f, "presentation"),
+            Action::MiniWindow => write!(f, "mini_window"),
+            Action::SessionPanel => write!(f, "session_panel"),
+            Action::Shortcuts => write!(f, "shortcuts"),
+            Action::CommandPalette => write!(f, "command_palette"),
+            Action::AddTime => write!(f, "add_time"),
+            Action::RemoveTime => write!(f, "remove_time"),
+        }
+    }
+}
+
+impl KeyBinding {
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn ctrl(key: Key) -> Self {
+        Self {
+            ctrl: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn from_input(key: Key, modifiers: Modifiers) -> Self {
+        Self {
+            key,
+            ctrl: modifiers.command,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+        }
+    }
+
+    // Ctrl maps to Cmd on macOS
+    pub fn modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::NONE;
+        if self.ctrl {
+            modifiers = modifiers | Modifiers::COMMAND;
+        }
+        if self.shift {
+            modifiers = modifiers | Modifiers::SHIFT;
+        }
+        if self.alt {
+            modifiers = modifiers | Modifiers::ALT;
+        }
+        modifiers
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = value.split('+').collect();
+        let name = parts.pop().unwrap_or_default();
+        let key = Key::from_name(name).ok_or_else(|| format!("Unknown key '{}'", name))?;
+
+        let mut binding = KeyBinding::new(key);
+        for modifier in parts {
+            match modifier {
+                "Ctrl" => binding.ctrl = true,
+                "Shift" => binding.shift = true,
+                "Alt" => binding.alt = true,
+                _ => return Err(format!("Unknown modifier '{}'", modifier)),
+            }
+        }
+
+        Ok(binding)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_binding()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> Option<KeyBinding> {
+        self.bindings
+            .iter()
+            .find(|(v, _)| *v == action)
+            .and_then(|(_, binding)| *binding)
+    }
+
+    // A key can only trigger one action, so rebinding steals it
+    pub fn set(&mut self, action: Action, binding: Option<KeyBinding>) {
+        for (v, existing) in self.bindings.iter_mut() {
+            if *v == action {
+                *existing = binding;
+            } else if binding.is_some() && *existing == binding {
+                *existing = None;
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Action, Option<KeyBinding>)> + '_ {
+        self.bindings.iter().copied()
+    }
+
+    // Bindings with modifiers come first so that Ctrl+S
+    // is not also read as a plain S
+    pub fn by_specificity(&self) -> Vec<(Action, KeyBinding)> {
+        let mut bindings: Vec<_> = self
+            .bindings
+            .iter()
+            .filter_map(|(action, binding)| Some((*action, (*binding)?)))
+            .collect();
+        bindings.sort_by_key(|(_, binding)| !(binding.ctrl || binding.shift || binding.alt));
+        bindings
+    }
+}
+
+// Only the listed actions are changed from their defaults,
+// e.g. "start_pause:Enter,save:Ctrl+Shift+S,restart:none"
+pub fn parse_keybindings(line: &str) -> Result<KeyBindings, String> {
+    let mut bindings = KeyBindings::default();
+
+    for entry in line.split(',').filter(|entry| !entry.is_empty()) {
+        let (action, binding) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("Missing ':' in key binding '{}'", entry))?;
+        let action = action.parse::<Action>()?;
+        let binding = match binding {
+            "none" => None,
+            value => Some(value.parse::<KeyBinding>()?),
+        };
+        bindings.set(action, binding);
+    }
+
+    Ok(bindings)
+}
+
+pub fn keybindings_to_string(bindings: &KeyBindings) -> String {
+    bindings
+        .iter()
+        .map(|(action, binding)| match binding {
+            Some(binding) => format!("{}:{}", action, binding),
+            None => format!("{}:none", action),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
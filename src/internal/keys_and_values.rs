@@ -68,12 +68,20 @@ impl KeysAndValues {
         }
     }
 
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.map.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
     pub fn set<T, F>(&mut self, key: &str, value: &T, conversion: F)
     where
         F: Fn(&T) -> String,
     {
         self.map.insert(key.to_string(), conversion(value));
     }
+
+    pub fn remove(&mut self, key: &str) {
+        self.map.remove(key);
+    }
 }
 
 fn parse(line: &str) -> Option<(String, String)> {
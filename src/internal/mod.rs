@@ -1,7 +1,31 @@
 mod keys_and_values;
+#[cfg(all(test, target_os = "linux"))]
+mod test_bus;
 
+pub mod cli;
 pub mod clock;
 pub mod dirs;
+pub mod fuzzy;
+pub mod history;
+pub mod ipc;
+pub mod json;
+pub mod keybindings;
+pub mod mpris;
+pub mod mqtt;
+pub mod notifications;
+pub mod osc;
+pub mod profiles;
 pub mod queue;
+pub mod random;
+pub mod script;
+pub mod sequence;
+pub mod session;
 pub mod settings_parser;
+pub mod shuffle;
 pub mod sound;
+pub mod statistics;
+pub mod templates;
+pub mod theme;
+pub mod timer;
+pub mod title;
+pub mod webhooks;
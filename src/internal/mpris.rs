@@ -0,0 +1,434 @@
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MprisCommand {
+    PlayPause,
+    Play,
+    Pause,
+    Next,
+    Previous,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MprisEvent {
+    Command(MprisCommand),
+    // The bus could not be reached or the player not registered
+    Failed(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerState {
+    pub has_timers: bool,
+    pub playing: bool,
+    pub label: String,
+    pub index: usize,
+    pub round: u32,
+    // None for intervals that count up until ended by hand
+    pub length: Option<Duration>,
+    pub position: Duration,
+}
+
+// Serves org.mpris.MediaPlayer2 so media keys and desktop widgets
+// can drive the timer, commands come back through poll_event
+pub struct MprisPlayer {
+    state: Arc<Mutex<PlayerState>>,
+    changes: Sender<()>,
+    events: Receiver<MprisEvent>,
+}
+
+impl MprisPlayer {
+    // Connects in the background, failing comes back through poll_event
+    pub fn start_session() -> Self {
+        dbus::spawn(None)
+    }
+
+    // For a private bus, e.g. one started with dbus-run-session
+    pub fn start(address: &str) -> Self {
+        dbus::spawn(Some(address.to_string()))
+    }
+
+    pub fn update(&self, new_state: PlayerState) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        // The position moves every frame, clients poll it instead of
+        // being told, so only the rest is worth a PropertiesChanged
+        let changed = PlayerState {
+            position: state.position,
+            ..new_state.clone()
+        } != *state;
+        *state = new_state;
+        drop(state);
+
+        if changed {
+            let _ = self.changes.send(());
+        }
+    }
+
+    pub fn poll_event(&self) -> Option<MprisEvent> {
+        self.events.try_recv().ok()
+    }
+}
+
+impl PlayerState {
+    fn playback_status(&self) -> &'static str {
+        match (self.has_timers, self.playing) {
+            (false, _) => "Stopped",
+            (true, true) => "Playing",
+            (true, false) => "Paused",
+        }
+    }
+
+    fn title(&self) -> String {
+        match self.label.is_empty() {
+            true => format!("Interval {}", self.index + 1),
+            false => self.label.clone(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod dbus {
+    use std::collections::HashMap;
+    use std::process;
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use zbus::blocking::{Connection, ConnectionBuilder};
+    use zbus::dbus_interface;
+    use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+
+    use super::{MprisCommand, MprisEvent, MprisPlayer, PlayerState};
+
+    const BUS_NAME: &str = "org.mpris.MediaPlayer2.interval_timer";
+    const PATH: &str = "/org/mpris/MediaPlayer2";
+    const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+    const TRACK_PATH: &str = "/org/intervaltimer/interval";
+
+    struct Root;
+
+    #[dbus_interface(name = "org.mpris.MediaPlayer2")]
+    impl Root {
+        fn raise(&self) {}
+
+        fn quit(&self) {}
+
+        #[dbus_interface(property)]
+        fn can_quit(&self) -> bool {
+            false
+        }
+
+        #[dbus_interface(property)]
+        fn can_raise(&self) -> bool {
+            false
+        }
+
+        #[dbus_interface(property)]
+        fn has_track_list(&self) -> bool {
+            false
+        }
+
+        #[dbus_interface(property)]
+        fn identity(&self) -> String {
+            "Interval Timer".to_string()
+        }
+
+        #[dbus_interface(property)]
+        fn supported_uri_schemes(&self) -> Vec<String> {
+            vec![]
+        }
+
+        #[dbus_interface(property)]
+        fn supported_mime_types(&self) -> Vec<String> {
+            vec![]
+        }
+    }
+
+    struct Player {
+        state: Arc<Mutex<PlayerState>>,
+        events: Mutex<Sender<MprisEvent>>,
+    }
+
+    impl Player {
+        fn send(&self, command: MprisCommand) {
+            if let Ok(events) = self.events.lock() {
+                let _ = events.send(MprisEvent::Command(command));
+            }
+        }
+
+        fn state(&self) -> PlayerState {
+            self.state.lock().map(|v| v.clone()).unwrap_or_default()
+        }
+    }
+
+    #[dbus_interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl Player {
+        fn next(&self) {
+            self.send(MprisCommand::Next);
+        }
+
+        fn previous(&self) {
+            self.send(MprisCommand::Previous);
+        }
+
+        fn pause(&self) {
+            self.send(MprisCommand::Pause);
+        }
+
+        fn play_pause(&self) {
+            self.send(MprisCommand::PlayPause);
+        }
+
+        // A timer has nowhere to stop to but paused
+        fn stop(&self) {
+            self.send(MprisCommand::Pause);
+        }
+
+        fn play(&self) {
+            self.send(MprisCommand::Play);
+        }
+
+        fn seek(&self, _offset: i64) {}
+
+        fn set_position(&self, _track_id: ObjectPath<'_>, _position: i64) {}
+
+        fn open_uri(&self, _uri: &str) {}
+
+        #[dbus_interface(property)]
+        fn playback_status(&self) -> String {
+            self.state().playback_status().to_string()
+        }
+
+        #[dbus_interface(property)]
+        fn metadata(&self) -> HashMap<String, OwnedValue> {
+            metadata(&self.state())
+        }
+
+        #[dbus_interface(property)]
+        fn position(&self) -> i64 {
+            self.state().position.as_micros() as i64
+        }
+
+        #[dbus_interface(property)]
+        fn rate(&self) -> f64 {
+            1.0
+        }
+
+        #[dbus_interface(property)]
+        fn minimum_rate(&self) -> f64 {
+            1.0
+        }
+
+        #[dbus_interface(property)]
+        fn maximum_rate(&self) -> f64 {
+            1.0
+        }
+
+        #[dbus_interface(property)]
+        fn volume(&self) -> f64 {
+            1.0
+        }
+
+        #[dbus_interface(property)]
+        fn can_go_next(&self) -> bool {
+            self.state().has_timers
+        }
+
+        #[dbus_interface(property)]
+        fn can_go_previous(&self) -> bool {
+            self.state().has_timers
+        }
+
+        #[dbus_interface(property)]
+        fn can_play(&self) -> bool {
+            self.state().has_timers
+        }
+
+        #[dbus_interface(property)]
+        fn can_pause(&self) -> bool {
+            self.state().has_timers
+        }
+
+        #[dbus_interface(property)]
+        fn can_seek(&self) -> bool {
+            false
+        }
+
+        #[dbus_interface(property)]
+        fn can_control(&self) -> bool {
+            true
+        }
+    }
+
+    fn metadata(state: &PlayerState) -> HashMap<String, OwnedValue> {
+        let mut metadata = HashMap::new();
+
+        // Round and index together, so every pass gets its own track
+        let track_id = format!("{}/r{}i{}", TRACK_PATH, state.round, state.index);
+        if let Ok(track_id) = ObjectPath::try_from(track_id) {
+            metadata.insert("mpris:trackid".to_string(), Value::from(track_id).into());
+        }
+        if let Some(length) = state.length {
+            let length = length.as_micros() as i64;
+            metadata.insert("mpris:length".to_string(), Value::from(length).into());
+        }
+        metadata.insert("xesam:title".to_string(), Value::from(state.title()).into());
+        metadata.insert(
+            "xesam:album".to_string(),
+            Value::from(format!("Round {}", state.round + 1)).into(),
+        );
+        metadata.insert(
+            "xesam:artist".to_string(),
+            Value::from(vec!["Interval Timer".to_string()]).into(),
+        );
+        metadata
+    }
+
+    pub fn spawn(address: Option<String>) -> MprisPlayer {
+        let state = Arc::new(Mutex::new(PlayerState::default()));
+        let (changes, receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+
+        let shared = state.clone();
+        thread::spawn(
+            move || match start(address, shared.clone(), event_sender.clone()) {
+                Ok(connection) => emit_changes(connection, receiver, shared),
+                Err(e) => {
+                    let _ = event_sender.send(MprisEvent::Failed(e));
+                }
+            },
+        );
+
+        MprisPlayer {
+            state,
+            changes,
+            events,
+        }
+    }
+
+    fn start(
+        address: Option<String>,
+        state: Arc<Mutex<PlayerState>>,
+        events: Sender<MprisEvent>,
+    ) -> Result<Connection, String> {
+        let player = Player {
+            state,
+            events: Mutex::new(events),
+        };
+
+        let builder = match &address {
+            Some(address) => ConnectionBuilder::address(address.as_str()),
+            None => ConnectionBuilder::session(),
+        };
+        let connection = builder
+            .and_then(|builder| builder.serve_at(PATH, Root))
+            .and_then(|builder| builder.serve_at(PATH, player))
+            .and_then(|builder| builder.build())
+            .map_err(|e| e.to_string())?;
+
+        // The spec asks further instances to add a unique suffix
+        if connection.request_name(BUS_NAME).is_err() {
+            let name = format!("{}.instance{}", BUS_NAME, process::id());
+            connection.request_name(name).map_err(|e| e.to_string())?;
+        }
+        Ok(connection)
+    }
+
+    fn emit_changes(connection: Connection, changes: Receiver<()>, state: Arc<Mutex<PlayerState>>) {
+        for () in changes {
+            let Ok(state) = state.lock().map(|v| v.clone()) else {
+                return;
+            };
+
+            let mut changed: HashMap<&str, Value> = HashMap::new();
+            changed.insert("PlaybackStatus", Value::from(state.playback_status()));
+            changed.insert("Metadata", Value::from(metadata(&state)));
+            for property in ["CanGoNext", "CanGoPrevious", "CanPlay", "CanPause"] {
+                changed.insert(property, Value::from(state.has_timers));
+            }
+            let invalidated: Vec<&str> = vec![];
+
+            let _ = connection.emit_signal(
+                None::<&str>,
+                PATH,
+                "org.freedesktop.DBus.Properties",
+                "PropertiesChanged",
+                &(PLAYER_INTERFACE, changed, invalidated),
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod dbus {
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
+
+    use super::{MprisEvent, MprisPlayer, PlayerState};
+
+    pub fn spawn(_address: Option<String>) -> MprisPlayer {
+        let (changes, _) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+        let message = "MPRIS needs D-Bus, which is only available on Linux";
+        let _ = event_sender.send(MprisEvent::Failed(message.to_string()));
+        MprisPlayer {
+            state: Arc::new(Mutex::new(PlayerState::default())),
+            changes,
+            events,
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use zbus::blocking::{ConnectionBuilder, Proxy};
+
+    use super::*;
+    use crate::internal::test_bus::{TestBus, wait_for};
+
+    const BUS_NAME: &str = "org.mpris.MediaPlayer2.interval_timer";
+    const PATH: &str = "/org/mpris/MediaPlayer2";
+    const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+    #[test]
+    fn reports_an_unreachable_bus() {
+        let player = MprisPlayer::start("unix:path=/nonexistent/bus");
+        assert!(matches!(
+            wait_for(|| player.poll_event()),
+            MprisEvent::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn serves_state_and_passes_on_commands() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let player = MprisPlayer::start(&bus.address);
+        player.update(PlayerState {
+            has_timers: true,
+            playing: true,
+            label: "Work".to_string(),
+            ..PlayerState::default()
+        });
+
+        let client = ConnectionBuilder::address(bus.address.as_str())
+            .and_then(|builder| builder.build())
+            .unwrap();
+        let proxy = Proxy::new(&client, BUS_NAME, PATH, PLAYER_INTERFACE).unwrap();
+
+        // The name shows up once the worker has registered it
+        let status = wait_for(|| proxy.get_property::<String>("PlaybackStatus").ok());
+        assert_eq!(status, "Playing");
+
+        proxy.call::<_, _, ()>("Next", &()).unwrap();
+        assert_eq!(
+            wait_for(|| player.poll_event()),
+            MprisEvent::Command(MprisCommand::Next)
+        );
+    }
+}
@@ -0,0 +1,650 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::internal::json::Json;
+use crate::internal::settings_parser::MqttSettings;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MqttCommand {
+    Start,
+    Pause,
+    Next,
+    Prev,
+    LoadProfile(String),
+}
+
+// Publishes under one topic prefix and keeps reconnecting to the broker
+// in the background, commands come back through poll_command
+pub struct MqttClient {
+    outgoing: Sender<Outgoing>,
+    commands: Receiver<MqttCommand>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+enum Outgoing {
+    Publish(Publish),
+    Lost(String),
+    Disconnect,
+}
+
+#[derive(Clone)]
+struct Publish {
+    topic: String,
+    payload: String,
+    retain: bool,
+}
+
+struct Connection {
+    stream: TcpStream,
+    last_sent: Instant,
+}
+
+#[cfg(not(test))]
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+#[cfg(test)]
+const KEEP_ALIVE: Duration = Duration::from_secs(2);
+// Pings go out every half keepalive, so by now the broker should have answered one
+const READ_TIMEOUT: Duration = Duration::from_millis(KEEP_ALIVE.as_millis() as u64 * 3 / 2);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+#[cfg(not(test))]
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+#[cfg(test)]
+const RECONNECT_DELAY: Duration = Duration::from_millis(100);
+// Commands are a word and a profile name, nothing legitimate comes close
+const MAX_PACKET_SIZE: usize = 64 * 1024;
+const DISCOVERY_PREFIX: &str = "homeassistant";
+
+impl MqttClient {
+    pub fn start(settings: &MqttSettings) -> Result<Self, String> {
+        if settings.topic.is_empty() || settings.topic.contains(['#', '+']) {
+            return Err(format!("Invalid MQTT topic '{}'", settings.topic));
+        }
+        // Only checked here, the broker may still come up later
+        settings
+            .broker
+            .to_socket_addrs()
+            .map_err(|e| format!("Invalid MQTT broker '{}': {}", settings.broker, e))?;
+
+        let (outgoing, receiver) = mpsc::channel();
+        let (commands, command_receiver) = mpsc::channel();
+        let error = Arc::new(Mutex::new(None));
+
+        let settings = settings.clone();
+        let sender = outgoing.clone();
+        let shared_error = error.clone();
+        thread::spawn(move || run(settings, sender, receiver, commands, shared_error));
+
+        Ok(Self {
+            outgoing,
+            commands: command_receiver,
+            error,
+        })
+    }
+
+    // Relative to the topic prefix, retained values are sent again after reconnecting
+    pub fn publish(&self, topic: &str, payload: String, retain: bool) {
+        let _ = self.outgoing.send(Outgoing::Publish(Publish {
+            topic: topic.to_string(),
+            payload,
+            retain,
+        }));
+    }
+
+    pub fn poll_command(&self) -> Option<MqttCommand> {
+        self.commands.try_recv().ok()
+    }
+
+    // Why the broker is unreachable right now, if it is
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().ok().and_then(|error| error.clone())
+    }
+}
+
+impl Drop for MqttClient {
+    fn drop(&mut self) {
+        let _ = self.outgoing.send(Outgoing::Disconnect);
+    }
+}
+
+impl MqttCommand {
+    // e.g. "next" from {topic}/cmd/next, the payload names the profile to load
+    pub fn parse(command: &str, payload: &str) -> Option<Self> {
+        match command {
+            "start" => Some(MqttCommand::Start),
+            "pause" => Some(MqttCommand::Pause),
+            "next" => Some(MqttCommand::Next),
+            "prev" => Some(MqttCommand::Prev),
+            "load" if !payload.trim().is_empty() => {
+                Some(MqttCommand::LoadProfile(payload.trim().to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn run(
+    settings: MqttSettings,
+    sender: Sender<Outgoing>,
+    receiver: Receiver<Outgoing>,
+    commands: Sender<MqttCommand>,
+    error: Arc<Mutex<Option<String>>>,
+) {
+    let set_error = |message: Option<String>| {
+        if let Ok(mut error) = error.lock() {
+            *error = message;
+        }
+    };
+    let status_topic = format!("{}/status", settings.topic);
+    let mut retained: HashMap<String, String> = HashMap::new();
+
+    loop {
+        match connect(&settings, &sender, &commands) {
+            Ok(mut connection) => {
+                set_error(None);
+                let mut messages = vec![Publish {
+                    topic: status_topic.clone(),
+                    payload: "online".to_string(),
+                    retain: true,
+                }];
+                if settings.discovery {
+                    messages.extend(discovery(&settings.topic));
+                }
+                messages.extend(retained.iter().map(|(topic, payload)| Publish {
+                    topic: topic.clone(),
+                    payload: payload.clone(),
+                    retain: true,
+                }));
+
+                let sent = messages
+                    .iter()
+                    .try_for_each(|message| connection.send(&publish_packet(message)));
+                let result = match sent {
+                    Ok(()) => serve(&mut connection, &settings, &receiver, &mut retained),
+                    Err(e) => Err(e.to_string()),
+                };
+
+                match result {
+                    // Leaving on purpose does not trigger the last will
+                    Ok(()) => {
+                        let offline = Publish {
+                            topic: status_topic,
+                            payload: "offline".to_string(),
+                            retain: true,
+                        };
+                        let _ = connection.send(&publish_packet(&offline));
+                        let _ = connection.send(&[0xe0, 0x00]);
+                        let _ = connection.stream.shutdown(Shutdown::Both);
+                        return;
+                    }
+                    Err(message) => {
+                        let _ = connection.stream.shutdown(Shutdown::Both);
+                        set_error(Some(message));
+                    }
+                }
+            }
+            Err(e) => set_error(Some(e)),
+        }
+
+        // Keep track of the latest state while waiting to reconnect
+        let deadline = Instant::now() + RECONNECT_DELAY;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            match receiver.recv_timeout(timeout) {
+                Ok(Outgoing::Publish(message)) => {
+                    remember(&mut retained, &settings.topic, message);
+                }
+                Ok(Outgoing::Lost(_)) => {}
+                Ok(Outgoing::Disconnect) | Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+    }
+}
+
+// Ok when the app asked to disconnect, Err when the connection broke
+fn serve(
+    connection: &mut Connection,
+    settings: &MqttSettings,
+    receiver: &Receiver<Outgoing>,
+    retained: &mut HashMap<String, String>,
+) -> Result<(), String> {
+    loop {
+        let ping_at = connection.last_sent + KEEP_ALIVE / 2;
+        let timeout = ping_at.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok(Outgoing::Publish(message)) => {
+                let message = remember(retained, &settings.topic, message);
+                connection
+                    .send(&publish_packet(&message))
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok(Outgoing::Lost(message)) => return Err(message),
+            Ok(Outgoing::Disconnect) | Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            Err(RecvTimeoutError::Timeout) => {
+                connection.send(&[0xc0, 0x00]).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+}
+
+fn remember(retained: &mut HashMap<String, String>, prefix: &str, message: Publish) -> Publish {
+    let message = Publish {
+        topic: format!("{}/{}", prefix, message.topic),
+        ..message
+    };
+    if message.retain {
+        retained.insert(message.topic.clone(), message.payload.clone());
+    }
+    message
+}
+
+fn connect(
+    settings: &MqttSettings,
+    lost: &Sender<Outgoing>,
+    commands: &Sender<MqttCommand>,
+) -> Result<Connection, String> {
+    let address = settings
+        .broker
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or_else(|| format!("MQTT broker '{}' did not resolve", settings.broker))?;
+    let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+        .map_err(|e| format!("Could not connect to {}: {}", settings.broker, e))?;
+
+    let mut connection = Connection {
+        stream,
+        last_sent: Instant::now(),
+    };
+    connection
+        .send(&connect_packet(settings))
+        .map_err(|e| e.to_string())?;
+
+    connection
+        .stream
+        .set_read_timeout(Some(CONNECT_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    match read_packet(&mut connection.stream).map_err(|e| e.to_string())? {
+        (0x20, body) if body.len() == 2 && body[1] == 0 => {}
+        (0x20, body) if body.len() == 2 => {
+            return Err(format!("MQTT broker refused: {}", refusal(body[1])));
+        }
+        _ => return Err("Expected CONNACK from the MQTT broker".to_string()),
+    }
+    // Every ping gets an answer, silence for longer means the broker is gone
+    connection
+        .stream
+        .set_read_timeout(Some(READ_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let filter = format!("{}/cmd/+", settings.topic);
+    connection
+        .send(&subscribe_packet(&filter))
+        .map_err(|e| e.to_string())?;
+
+    let mut reader = connection.stream.try_clone().map_err(|e| e.to_string())?;
+    let prefix = format!("{}/cmd/", settings.topic);
+    let lost = lost.clone();
+    let commands = commands.clone();
+    thread::spawn(move || {
+        let error = loop {
+            let (header, body) = match read_packet(&mut reader) {
+                Ok(packet) => packet,
+                Err(e) => break e,
+            };
+            if header & 0xf0 != 0x30 {
+                continue;
+            }
+            let Some((topic, payload)) = parse_publish(header, &body) else {
+                continue;
+            };
+            let command = topic
+                .strip_prefix(&prefix)
+                .and_then(|command| MqttCommand::parse(command, &payload));
+            if let Some(command) = command
+                && commands.send(command).is_err()
+            {
+                return;
+            }
+        };
+        let message = match error.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+                "The MQTT broker stopped answering".to_string()
+            }
+            io::ErrorKind::UnexpectedEof => "Lost the MQTT broker".to_string(),
+            _ => format!("Lost the MQTT broker: {}", error),
+        };
+        let _ = lost.send(Outgoing::Lost(message));
+    });
+
+    Ok(connection)
+}
+
+impl Connection {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.stream.write_all(packet)?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+}
+
+fn refusal(code: u8) -> &'static str {
+    match code {
+        1 => "unsupported protocol version",
+        2 => "client id rejected",
+        3 => "server unavailable",
+        4 => "bad user name or password",
+        5 => "not authorized",
+        _ => "unknown reason",
+    }
+}
+
+// Home Assistant picks these up and groups them under one device
+fn discovery(prefix: &str) -> Vec<Publish> {
+    let node: String = prefix
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    let device = || {
+        Json::object(vec![
+            ("identifiers", Json::Array(vec![node.as_str().into()])),
+            ("name", "Interval Timer".into()),
+        ])
+    };
+    let entity = |component: &str, id: &str, name: &str, mut fields: Vec<(&str, Json)>| {
+        fields.extend([
+            ("name", name.into()),
+            ("unique_id", format!("{}_{}", node, id).into()),
+            ("availability_topic", format!("{}/status", prefix).into()),
+            ("device", device()),
+        ]);
+        Publish {
+            topic: format!("{}/{}/{}/{}/config", DISCOVERY_PREFIX, component, node, id),
+            payload: Json::object(fields).to_string(),
+            retain: true,
+        }
+    };
+
+    let mut messages = vec![
+        entity(
+            "sensor",
+            "state",
+            "State",
+            vec![("state_topic", format!("{}/state", prefix).into())],
+        ),
+        entity(
+            "sensor",
+            "interval",
+            "Interval",
+            vec![
+                ("state_topic", format!("{}/interval", prefix).into()),
+                ("value_template", "{{ value_json.label }}".into()),
+                (
+                    "json_attributes_topic",
+                    format!("{}/interval", prefix).into(),
+                ),
+            ],
+        ),
+        entity(
+            "sensor",
+            "remaining",
+            "Remaining",
+            vec![
+                ("state_topic", format!("{}/remaining", prefix).into()),
+                ("unit_of_measurement", "s".into()),
+                ("device_class", "duration".into()),
+            ],
+        ),
+    ];
+    for (id, name) in [
+        ("start", "Start"),
+        ("pause", "Pause"),
+        ("next", "Next"),
+        ("prev", "Previous"),
+    ] {
+        messages.push(entity(
+            "button",
+            id,
+            name,
+            vec![("command_topic", format!("{}/cmd/{}", prefix, id).into())],
+        ));
+    }
+    messages
+}
+
+// MQTT 3.1.1 with a clean session, everything at QoS 0
+fn connect_packet(settings: &MqttSettings) -> Vec<u8> {
+    let mut flags = 0x02 | 0x04 | 0x20; // clean session, will, retained will
+    if !settings.username.is_empty() {
+        flags |= 0x80;
+        if !settings.password.is_empty() {
+            flags |= 0x40;
+        }
+    }
+
+    let mut body = vec![];
+    write_string(&mut body, "MQTT");
+    body.push(4);
+    body.push(flags);
+    body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+    write_string(&mut body, &format!("interval-timer-{}", std::process::id()));
+    write_string(&mut body, &format!("{}/status", settings.topic));
+    write_string(&mut body, "offline");
+    if !settings.username.is_empty() {
+        write_string(&mut body, &settings.username);
+        if !settings.password.is_empty() {
+            write_string(&mut body, &settings.password);
+        }
+    }
+    packet(0x10, body)
+}
+
+fn publish_packet(message: &Publish) -> Vec<u8> {
+    let mut body = vec![];
+    write_string(&mut body, &message.topic);
+    body.extend_from_slice(message.payload.as_bytes());
+    packet(0x30 | message.retain as u8, body)
+}
+
+fn subscribe_packet(filter: &str) -> Vec<u8> {
+    let mut body = vec![0x00, 0x01]; // packet id
+    write_string(&mut body, filter);
+    body.push(0); // QoS 0
+    packet(0x82, body)
+}
+
+fn parse_publish(header: u8, body: &[u8]) -> Option<(String, String)> {
+    let length = u16::from_be_bytes([*body.first()?, *body.get(1)?]) as usize;
+    let topic = body.get(2..2 + length)?;
+    // QoS 1 and 2 carry a packet id before the payload
+    let skip = match (header >> 1) & 0x03 {
+        0 => 0,
+        _ => 2,
+    };
+    let payload = body.get(2 + length + skip..)?;
+    Some((
+        String::from_utf8_lossy(topic).to_string(),
+        String::from_utf8_lossy(payload).to_string(),
+    ))
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut packet = vec![header];
+    // Remaining length, seven bits at a time
+    let mut length = body.len();
+    loop {
+        let byte = (length % 128) as u8;
+        length /= 128;
+        match length {
+            0 => {
+                packet.push(byte);
+                break;
+            }
+            _ => packet.push(byte | 0x80),
+        }
+    }
+    packet.extend(body);
+    packet
+}
+
+fn read_packet(stream: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut byte = [0; 1];
+    stream.read_exact(&mut byte)?;
+    let header = byte[0];
+
+    let mut length = 0;
+    for shift in [0, 7, 14, 21] {
+        stream.read_exact(&mut byte)?;
+        length |= ((byte[0] & 0x7f) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+
+    if length > MAX_PACKET_SIZE {
+        let message = format!("MQTT packet of {} bytes is too large", length);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body)?;
+    Ok((header, body))
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    struct Broker {
+        listener: TcpListener,
+    }
+
+    impl Broker {
+        fn start() -> (Self, MqttSettings) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let settings = MqttSettings {
+                enabled: true,
+                broker: listener.local_addr().unwrap().to_string(),
+                topic: "gym/timer".to_string(),
+                discovery: false,
+                ..MqttSettings::default()
+            };
+            (Self { listener }, settings)
+        }
+
+        // Takes the next client up to its subscription
+        fn accept(&self) -> TcpStream {
+            let (mut stream, _) = self.listener.accept().unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+            let (header, _) = read_packet(&mut stream).unwrap();
+            assert_eq!(header, 0x10);
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+            loop {
+                let (header, body) = read_packet(&mut stream).unwrap();
+                if header == 0x82 {
+                    let filter = String::from_utf8_lossy(&body[4..]).to_string();
+                    assert!(filter.starts_with("gym/timer/cmd/+"));
+                    return stream;
+                }
+            }
+        }
+    }
+
+    fn publish(topic: &str, payload: &str) -> Vec<u8> {
+        publish_packet(&Publish {
+            topic: topic.to_string(),
+            payload: payload.to_string(),
+            retain: false,
+        })
+    }
+
+    fn wait_for_command(client: &MqttClient) -> MqttCommand {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(command) = client.poll_command() {
+                return command;
+            }
+            assert!(Instant::now() < deadline, "no command received");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn passes_on_commands_from_the_broker() {
+        let (broker, settings) = Broker::start();
+        let client = MqttClient::start(&settings).unwrap();
+        let mut stream = broker.accept();
+
+        stream
+            .write_all(&publish("gym/timer/cmd/load", "Legs"))
+            .unwrap();
+        stream
+            .write_all(&publish("gym/timer/cmd/next", ""))
+            .unwrap();
+        assert_eq!(
+            wait_for_command(&client),
+            MqttCommand::LoadProfile("Legs".to_string())
+        );
+        assert_eq!(wait_for_command(&client), MqttCommand::Next);
+    }
+
+    #[test]
+    fn reconnects_when_pings_go_unanswered() {
+        let (broker, settings) = Broker::start();
+        let client = MqttClient::start(&settings).unwrap();
+        let started = Instant::now();
+        let _silent = broker.accept();
+
+        // Nothing comes back for the pings, so the client gives up and tries again
+        let _second = broker.accept();
+        assert!(started.elapsed() >= READ_TIMEOUT);
+        drop(client);
+    }
+
+    #[test]
+    fn answered_pings_keep_the_connection() {
+        let (broker, settings) = Broker::start();
+        let client = MqttClient::start(&settings).unwrap();
+        let mut stream = broker.accept();
+
+        let deadline = Instant::now() + READ_TIMEOUT * 2;
+        while Instant::now() < deadline {
+            let (header, _) = read_packet(&mut stream).unwrap();
+            if header == 0xc0 {
+                stream.write_all(&[0xd0, 0x00]).unwrap();
+            }
+        }
+        assert_eq!(client.error(), None);
+        stream
+            .write_all(&publish("gym/timer/cmd/start", ""))
+            .unwrap();
+        assert_eq!(wait_for_command(&client), MqttCommand::Start);
+    }
+
+    #[test]
+    fn refuses_oversized_packets() {
+        // Remaining length of 268 435 455, the most MQTT can express
+        let mut packet: &[u8] = &[0x30, 0xff, 0xff, 0xff, 0x7f];
+        let error = read_packet(&mut packet).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let mut packet: &[u8] = &[0xd0, 0x00];
+        assert_eq!(read_packet(&mut packet).unwrap(), (0xd0, vec![]));
+    }
+}
@@ -0,0 +1,360 @@
+use std::sync::mpsc::{Receiver, Sender};
+
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+    // Offers Pause and Skip buttons that come back as NotificationAction
+    pub actions: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationAction {
+    Pause,
+    Skip,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotifierEvent {
+    Action(NotificationAction),
+    // The bus or the notification daemon could not be reached
+    Failed(String),
+}
+
+// Talks to org.freedesktop.Notifications from background threads,
+// so a slow or missing notification daemon never stalls a frame
+pub struct Notifier {
+    sender: Sender<Notification>,
+    events: Receiver<NotifierEvent>,
+}
+
+impl Notifier {
+    // Connects in the background, failing comes back through poll_event
+    pub fn connect_session() -> Self {
+        dbus::spawn(None)
+    }
+
+    // For a private bus, e.g. one started with dbus-run-session
+    pub fn connect(address: &str) -> Self {
+        dbus::spawn(Some(address.to_string()))
+    }
+
+    pub fn notify(&self, notification: Notification) {
+        // The sending thread is gone once the bus went away,
+        // there is nothing left to do but drop the notification
+        let _ = self.sender.send(notification);
+    }
+
+    pub fn poll_event(&self) -> Option<NotifierEvent> {
+        self.events.try_recv().ok()
+    }
+}
+
+impl NotificationAction {
+    fn key(&self) -> &'static str {
+        match self {
+            NotificationAction::Pause => "pause",
+            NotificationAction::Skip => "skip",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "pause" => Some(NotificationAction::Pause),
+            "skip" => Some(NotificationAction::Skip),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod dbus {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    use async_io::Timer;
+    use futures_util::StreamExt;
+    use futures_util::future::{self, Either};
+    use zbus::SignalStream;
+    use zbus::blocking::{Connection, ConnectionBuilder, Proxy};
+    use zbus::zvariant::Value;
+
+    use super::{Notification, NotificationAction, Notifier, NotifierEvent};
+
+    const APP_NAME: &str = "Interval Timer";
+    const DESTINATION: &str = "org.freedesktop.Notifications";
+    const PATH: &str = "/org/freedesktop/Notifications";
+    const INTERFACE: &str = "org.freedesktop.Notifications";
+    // Milliseconds, -1 leaves it to the notification daemon
+    const EXPIRE_TIMEOUT: i32 = -1;
+    // How long the action listener waits before checking it is still wanted
+    const LISTEN_TIMEOUT: Duration = Duration::from_millis(250);
+
+    pub fn spawn(address: Option<String>) -> Notifier {
+        let (sender, notifications) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+
+        thread::spawn(move || {
+            if let Err(e) = run(address, notifications, event_sender.clone()) {
+                let _ = event_sender.send(NotifierEvent::Failed(e));
+            }
+        });
+
+        Notifier { sender, events }
+    }
+
+    fn run(
+        address: Option<String>,
+        notifications: Receiver<Notification>,
+        events: Sender<NotifierEvent>,
+    ) -> Result<(), String> {
+        let connection = match address {
+            Some(address) => {
+                ConnectionBuilder::address(address.as_str()).and_then(|builder| builder.build())
+            }
+            None => Connection::session(),
+        }
+        .map_err(|e| e.to_string())?;
+
+        // Fails early when nothing on the bus provides notifications
+        let proxy =
+            Proxy::new(&connection, DESTINATION, PATH, INTERFACE).map_err(|e| e.to_string())?;
+        proxy
+            .call::<_, _, (String, String, String, String)>("GetServerInformation", &())
+            .map_err(|e| e.to_string())?;
+        let signals = async_io::block_on(proxy.inner().receive_signal("ActionInvoked"))
+            .map_err(|e| e.to_string())?;
+
+        // Only actions on our latest notification are ours to handle
+        let current_id = Arc::new(AtomicU32::new(0));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let listener = {
+            let (current_id, closed) = (current_id.clone(), closed.clone());
+            thread::spawn(move || listen(signals, current_id, events, closed))
+        };
+
+        send_notifications(&proxy, notifications, current_id);
+        // The notifier was dropped, take the listener and its
+        // hold on the connection down with it
+        closed.store(true, Ordering::Relaxed);
+        let _ = listener.join();
+        Ok(())
+    }
+
+    fn listen(
+        mut signals: SignalStream<'static>,
+        current_id: Arc<AtomicU32>,
+        events: Sender<NotifierEvent>,
+        closed: Arc<AtomicBool>,
+    ) {
+        while !closed.load(Ordering::Relaxed) {
+            let timeout = Timer::after(LISTEN_TIMEOUT);
+            let message = match async_io::block_on(future::select(signals.next(), timeout)) {
+                Either::Left((Some(message), _)) => message,
+                Either::Left((None, _)) => break,
+                Either::Right(_) => continue,
+            };
+
+            let Ok((id, key)) = message.body::<(u32, String)>() else {
+                continue;
+            };
+            if id != current_id.load(Ordering::Relaxed) {
+                continue;
+            }
+            let Some(action) = NotificationAction::from_key(&key) else {
+                continue;
+            };
+            if events.send(NotifierEvent::Action(action)).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn send_notifications(
+        proxy: &Proxy,
+        notifications: Receiver<Notification>,
+        current_id: Arc<AtomicU32>,
+    ) {
+        for notification in notifications {
+            let actions = match notification.actions {
+                true => vec![
+                    NotificationAction::Pause.key(),
+                    "Pause",
+                    NotificationAction::Skip.key(),
+                    "Skip",
+                ],
+                false => vec![],
+            };
+            let hints: HashMap<&str, Value> = HashMap::new();
+
+            // Replacing the previous notification keeps them from piling up
+            let replaces_id = current_id.load(Ordering::Relaxed);
+            let body = (
+                APP_NAME,
+                replaces_id,
+                "",
+                notification.summary.as_str(),
+                notification.body.as_str(),
+                actions,
+                hints,
+                EXPIRE_TIMEOUT,
+            );
+            if let Ok(id) = proxy.call::<_, _, u32>("Notify", &body) {
+                current_id.store(id, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod dbus {
+    use std::sync::mpsc;
+
+    use super::{Notifier, NotifierEvent};
+
+    pub fn spawn(_address: Option<String>) -> Notifier {
+        let (sender, _) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+        let message = "Desktop notifications need D-Bus, which is only available on Linux";
+        let _ = event_sender.send(NotifierEvent::Failed(message.to_string()));
+        Notifier { sender, events }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::sync::mpsc::{self, Sender};
+
+    use zbus::blocking::fdo::DBusProxy;
+    use zbus::blocking::{Connection, ConnectionBuilder};
+    use zbus::dbus_interface;
+    use zbus::zvariant::Value;
+
+    use super::*;
+    use crate::internal::test_bus::{TestBus, wait_for};
+
+    const PATH: &str = "/org/freedesktop/Notifications";
+    const INTERFACE: &str = "org.freedesktop.Notifications";
+
+    struct Daemon {
+        summaries: Mutex<Sender<String>>,
+    }
+
+    #[dbus_interface(name = "org.freedesktop.Notifications")]
+    impl Daemon {
+        fn get_server_information(&self) -> (String, String, String, String) {
+            let info = ["test", "interval-timer", "0", "1.2"];
+            info.map(str::to_string).into()
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &self,
+            _app_name: &str,
+            _replaces_id: u32,
+            _app_icon: &str,
+            summary: &str,
+            _body: &str,
+            _actions: Vec<&str>,
+            _hints: HashMap<&str, Value<'_>>,
+            _expire_timeout: i32,
+        ) -> u32 {
+            if let Ok(summaries) = self.summaries.lock() {
+                let _ = summaries.send(summary.to_string());
+            }
+            7
+        }
+    }
+
+    fn serve(address: &str, summaries: Sender<String>) -> Connection {
+        let daemon = Daemon {
+            summaries: Mutex::new(summaries),
+        };
+        ConnectionBuilder::address(address)
+            .and_then(|builder| builder.serve_at(PATH, daemon))
+            .and_then(|builder| builder.name(INTERFACE))
+            .and_then(|builder| builder.build())
+            .unwrap()
+    }
+
+    fn notification(summary: &str) -> Notification {
+        Notification {
+            summary: summary.to_string(),
+            body: String::new(),
+            actions: true,
+        }
+    }
+
+    #[test]
+    fn reports_a_missing_daemon() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let notifier = Notifier::connect(&bus.address);
+        assert!(matches!(
+            wait_for(|| notifier.poll_event()),
+            NotifierEvent::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn reports_an_unreachable_bus() {
+        let notifier = Notifier::connect("unix:path=/nonexistent/bus");
+        assert!(matches!(
+            wait_for(|| notifier.poll_event()),
+            NotifierEvent::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn stops_listening_once_dropped() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let (summaries, received) = mpsc::channel();
+        let daemon = serve(&bus.address, summaries);
+        let bus_names = DBusProxy::new(&daemon).unwrap();
+        let names = bus_names.list_names().unwrap().len();
+
+        let notifier = Notifier::connect(&bus.address);
+        notifier.notify(notification("Go"));
+        assert_eq!(wait_for(|| received.try_recv().ok()), "Go");
+        assert!(bus_names.list_names().unwrap().len() > names);
+
+        // The notifier's connection leaves the bus along with it
+        drop(notifier);
+        wait_for(|| (bus_names.list_names().unwrap().len() == names).then_some(()));
+    }
+
+    #[test]
+    fn notifies_and_passes_on_actions() {
+        let Some(bus) = TestBus::start() else {
+            return;
+        };
+        let (summaries, received) = mpsc::channel();
+        let daemon = serve(&bus.address, summaries);
+
+        let notifier = Notifier::connect(&bus.address);
+        notifier.notify(notification("3 s left"));
+        assert_eq!(wait_for(|| received.try_recv().ok()), "3 s left");
+
+        // Wait for the returned id to be stored before acting on it
+        notifier.notify(notification("2 s left"));
+        assert_eq!(wait_for(|| received.try_recv().ok()), "2 s left");
+
+        let skip = (7u32, "skip");
+        daemon
+            .emit_signal(None::<&str>, PATH, INTERFACE, "ActionInvoked", &skip)
+            .unwrap();
+        assert_eq!(
+            wait_for(|| notifier.poll_event()),
+            NotifierEvent::Action(NotificationAction::Skip)
+        );
+    }
+}
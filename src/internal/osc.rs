@@ -0,0 +1,319 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OscCommand {
+    Start,
+    Pause,
+    Next,
+    Prev,
+    Load(String),
+}
+
+// Sends to one target and listens on one address over UDP,
+// incoming control messages come back through poll_command
+pub struct Osc {
+    socket: UdpSocket,
+    target: Option<SocketAddr>,
+    commands: Receiver<OscCommand>,
+    stop: Arc<AtomicBool>,
+}
+
+// How often the listening thread checks whether it should stop
+const RECEIVE_TIMEOUT: Duration = Duration::from_millis(200);
+const MAX_PACKET_SIZE: usize = 1536;
+
+impl OscMessage {
+    pub fn new(address: &str, args: Vec<OscArg>) -> Self {
+        Self {
+            address: address.to_string(),
+            args,
+        }
+    }
+
+    // OSC 1.0: padded address, padded type tags, then big endian arguments
+    pub fn encode(&self) -> Vec<u8> {
+        let mut packet = vec![];
+        write_padded(&mut packet, &self.address);
+
+        let tags: String = self
+            .args
+            .iter()
+            .map(|arg| match arg {
+                OscArg::Int(_) => 'i',
+                OscArg::Float(_) => 'f',
+                OscArg::Str(_) => 's',
+            })
+            .collect();
+        write_padded(&mut packet, &format!(",{}", tags));
+
+        for arg in &self.args {
+            match arg {
+                OscArg::Int(value) => packet.extend_from_slice(&value.to_be_bytes()),
+                OscArg::Float(value) => packet.extend_from_slice(&value.to_be_bytes()),
+                OscArg::Str(value) => write_padded(&mut packet, value),
+            }
+        }
+        packet
+    }
+
+    // Bundles are flattened into the messages they carry
+    pub fn decode(packet: &[u8]) -> Result<Vec<OscMessage>, String> {
+        let mut reader = Reader {
+            data: packet,
+            pos: 0,
+        };
+        let address = reader.string()?;
+
+        if address == "#bundle" {
+            reader.bytes(8)?; // time tag, everything runs immediately
+            let mut messages = vec![];
+            while reader.pos < packet.len() {
+                let size = reader.int()?;
+                let size = usize::try_from(size).map_err(|_| "Negative bundle element size")?;
+                messages.extend(OscMessage::decode(reader.bytes(size)?)?);
+            }
+            return Ok(messages);
+        }
+
+        // Type tags are optional in very old senders
+        let tags = match reader.pos < packet.len() {
+            true => reader.string()?,
+            false => ",".to_string(),
+        };
+        let tags = tags
+            .strip_prefix(',')
+            .ok_or_else(|| format!("Invalid type tags '{}'", tags))?;
+
+        let args = tags
+            .chars()
+            .filter_map(|tag| match tag {
+                'i' => Some(reader.int().map(OscArg::Int)),
+                'f' => Some(reader.float().map(OscArg::Float)),
+                's' | 'S' => Some(reader.string().map(OscArg::Str)),
+                // Arguments without data
+                'T' | 'F' | 'N' | 'I' => None,
+                c => Some(Err(format!("Unsupported argument type '{}'", c))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(vec![OscMessage { address, args }])
+    }
+}
+
+impl OscCommand {
+    pub fn from_message(message: &OscMessage) -> Option<Self> {
+        match message.address.as_str() {
+            "/timer/start" => Some(OscCommand::Start),
+            "/timer/pause" => Some(OscCommand::Pause),
+            "/timer/next" => Some(OscCommand::Next),
+            "/timer/prev" => Some(OscCommand::Prev),
+            "/timer/load" => match message.args.first() {
+                Some(OscArg::Str(name)) => Some(OscCommand::Load(name.clone())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Osc {
+    // A listen port of 0 only sends
+    pub fn start(target: &str, bind: &str, listen_port: u16) -> Result<Self, String> {
+        let target = parse_target(target)?;
+        // Sending from its own socket lets the listener stay on loopback
+        let local: IpAddr = match target {
+            Some(SocketAddr::V6(_)) => Ipv6Addr::UNSPECIFIED.into(),
+            _ => Ipv4Addr::UNSPECIFIED.into(),
+        };
+        let socket = UdpSocket::bind((local, 0)).map_err(|e| e.to_string())?;
+
+        let (sender, commands) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        if listen_port != 0 {
+            let address = bind
+                .parse::<IpAddr>()
+                .map_err(|_| format!("Invalid OSC listen address '{}'", bind))?;
+            let receiver = UdpSocket::bind((address, listen_port))
+                .map_err(|e| format!("Could not listen on {}: {}", address, e))?;
+            receiver
+                .set_read_timeout(Some(RECEIVE_TIMEOUT))
+                .map_err(|e| e.to_string())?;
+            let stop = stop.clone();
+
+            thread::spawn(move || {
+                let mut buffer = [0; MAX_PACKET_SIZE];
+                while !stop.load(Ordering::Relaxed) {
+                    let Ok((size, _)) = receiver.recv_from(&mut buffer) else {
+                        continue;
+                    };
+                    let Ok(messages) = OscMessage::decode(&buffer[..size]) else {
+                        continue;
+                    };
+                    for command in messages.iter().filter_map(OscCommand::from_message) {
+                        if sender.send(command).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            socket,
+            target,
+            commands,
+            stop,
+        })
+    }
+
+    pub fn send(&self, message: &OscMessage) {
+        if let Some(target) = self.target {
+            // UDP gives no guarantees anyway, a lost message is not an error
+            let _ = self.socket.send_to(&message.encode(), target);
+        }
+    }
+
+    pub fn poll_command(&self) -> Option<OscCommand> {
+        self.commands.try_recv().ok()
+    }
+}
+
+impl Drop for Osc {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// e.g. "127.0.0.1:9000" or "lights.local:9000", empty sends nothing
+pub fn parse_target(target: &str) -> Result<Option<SocketAddr>, String> {
+    if target.is_empty() {
+        return Ok(None);
+    }
+    target
+        .to_socket_addrs()
+        .map_err(|e| format!("Invalid OSC target '{}': {}", target, e))?
+        .next()
+        .map(Some)
+        .ok_or_else(|| format!("OSC target '{}' did not resolve", target))
+}
+
+fn write_padded(packet: &mut Vec<u8>, value: &str) {
+    packet.extend_from_slice(value.as_bytes());
+    // At least one null terminator, then up to a multiple of four
+    let padding = 4 - value.len() % 4;
+    packet.extend(std::iter::repeat_n(0, padding));
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self.pos + count;
+        let bytes = self.data.get(self.pos..end).ok_or("Packet ended early")?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn int(&mut self) -> Result<i32, String> {
+        let bytes = self.bytes(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn float(&mut self) -> Result<f32, String> {
+        let bytes = self.bytes(4)?;
+        Ok(f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let rest = self.data.get(self.pos..).unwrap_or_default();
+        let length = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("Unterminated string")?;
+        let value = String::from_utf8_lossy(&rest[..length]).to_string();
+        self.bytes((length / 4 + 1) * 4)?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn free_port() -> u16 {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.local_addr().unwrap().port()
+    }
+
+    #[test]
+    fn encodes_and_decodes() {
+        let message = OscMessage::new(
+            "/timer/interval/start",
+            vec![
+                OscArg::Int(2),
+                OscArg::Str("Rest".into()),
+                OscArg::Float(0.5),
+            ],
+        );
+        assert_eq!(
+            OscMessage::decode(&message.encode()).unwrap(),
+            vec![message]
+        );
+    }
+
+    #[test]
+    fn sends_and_receives_over_loopback() {
+        let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let target = peer.local_addr().unwrap().to_string();
+        let port = free_port();
+        let osc = Osc::start(&target, "127.0.0.1", port).unwrap();
+
+        osc.send(&OscMessage::new("/timer/start", vec![]));
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        let (size, _) = peer.recv_from(&mut buffer).unwrap();
+        let messages = OscMessage::decode(&buffer[..size]).unwrap();
+        assert_eq!(messages[0].address, "/timer/start");
+
+        let load = OscMessage::new("/timer/load", vec![OscArg::Str("Legs".into())]);
+        peer.send_to(&load.encode(), ("127.0.0.1", port)).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let command = loop {
+            if let Some(command) = osc.poll_command() {
+                break command;
+            }
+            assert!(Instant::now() < deadline, "no command received");
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(command, OscCommand::Load("Legs".into()));
+    }
+
+    #[test]
+    fn rejects_invalid_listen_addresses() {
+        assert!(Osc::start("", "localhost", free_port()).is_err());
+        assert!(Osc::start("", "localhost", 0).is_ok());
+    }
+}
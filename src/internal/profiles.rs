@@ -0,0 +1,98 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::internal::keys_and_values::{KeysAndValues, ParseError};
+use crate::internal::settings_parser::{ProgressStyle, parse_timers, timers_to_string};
+use crate::internal::templates::sanitize_name;
+use crate::internal::timer::Timer;
+
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub timers: Vec<Timer>,
+    pub progress_style: Option<ProgressStyle>,
+}
+
+impl Profile {
+    pub fn new_from_file(dir: &str, name: &str) -> Result<Self, String> {
+        let file_content = fs::read_to_string(profile_path(dir, name)?)
+            .map_err(|e| format!("Could not read profile '{}': {}", name, e))?;
+
+        let kv = KeysAndValues::new_from_str(&file_content).map_err(|e| e.to_string())?;
+        let timers = kv.get("timers", parse_timers).map_err(|e| e.to_string())?;
+
+        // Profiles without a style follow the global one
+        let progress_style = match kv.get("progress_style", |v| v.parse::<ProgressStyle>()) {
+            Ok(value) => Some(value),
+            Err(ParseError::MissingKey(_)) => None,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            timers,
+            progress_style,
+        })
+    }
+
+    pub fn write_to_file(&self, dir: &str) -> Result<(), String> {
+        let mut kv = KeysAndValues::new();
+        kv.set("timers", &self.timers, |v| timers_to_string(v));
+        if let Some(progress_style) = &self.progress_style {
+            kv.set("progress_style", progress_style, |v| v.to_string());
+        }
+
+        fs::create_dir_all(dir).map_err(|v| v.to_string())?;
+        kv.write_to_file(&profile_path(dir, &self.name)?)
+            .map_err(|v| v.to_string())
+    }
+}
+
+pub fn list_profiles(dir: &str) -> Result<Vec<String>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(value) => value,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("Unhandled io error: {}", e)),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|v| v == "txt"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+
+    Ok(names)
+}
+
+// Names can arrive over the socket, OSC or MQTT, so anything that could
+// point outside the profiles directory is refused
+fn profile_path(dir: &str, name: &str) -> Result<String, String> {
+    if name.is_empty() || sanitize_name(name) != name {
+        return Err(format!("Invalid profile name '{}'", name));
+    }
+
+    Ok(Path::new(dir)
+        .join(format!("{}.txt", name))
+        .to_string_lossy()
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_profiles_inside_their_dir() {
+        assert_eq!(
+            profile_path("/profiles", "Legs day"),
+            Ok("/profiles/Legs day.txt".to_string())
+        );
+        for name in ["", "../../x", "/abs/path", "a\\b", " padded "] {
+            assert!(profile_path("/profiles", name).is_err(), "{}", name);
+        }
+        assert!(Profile::new_from_file("/profiles", "../secret").is_err());
+    }
+}
@@ -4,6 +4,12 @@ pub struct Queue<T> {
     front_index: usize,
 }
 
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Queue<T> {
     pub fn new() -> Self {
         Self {
@@ -28,6 +34,18 @@ impl<T> Queue<T> {
         self.items.get_mut(self.front_index)
     }
 
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn peek_next(&self) -> Option<&T> {
+        if self.items.is_empty() {
+            return None;
+        }
+
+        self.items.get((self.front_index + 1) % self.items.len())
+    }
+
     pub fn prev(&mut self) {
         if !self.items.is_empty() {
             if self.front_index == 0 {
@@ -45,11 +63,37 @@ impl<T> Queue<T> {
         }
     }
 
+    // Right after the current item, which stays current
+    pub fn insert_next(&mut self, value: T) {
+        let index = match self.items.is_empty() {
+            true => 0,
+            false => self.front_index + 1,
+        };
+        self.items.insert(index, value);
+    }
+
     pub fn set(&mut self, values: Vec<T>) {
         self.items = values.into_iter().collect();
     }
 
+    pub fn index(&self) -> usize {
+        self.front_index
+    }
+
+    pub fn set_index(&mut self, index: usize) -> bool {
+        if index >= self.items.len() {
+            return false;
+        }
+
+        self.front_index = index;
+        true
+    }
+
+    pub fn is_first(&self) -> bool {
+        self.front_index == 0
+    }
+
     pub fn is_last(&self) -> bool {
-        self.front_index == self.items.len() - 1
+        !self.items.is_empty() && self.front_index == self.items.len() - 1
     }
 }
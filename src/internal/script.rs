@@ -0,0 +1,397 @@
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use std::time::Duration;
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{AST, Array, CallFnOptions, Dynamic, Engine, FLOAT, INT, Map, Scope};
+
+use crate::internal::random::Rng;
+use crate::internal::settings_parser::parse_color;
+use crate::internal::timer::{Advance, Timer, TimerKind, sanitize_label};
+
+// What a hook asked for, applied to the interval that is current
+// once the hook returns
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptAction {
+    Skip,
+    // Negative shortens
+    Extend(i64),
+    Insert(Timer),
+    Pause,
+}
+
+// The interval a hook is about, with the whole sequence for context
+pub struct ScriptEvent {
+    pub index: usize,
+    pub round: u32,
+    pub label: String,
+    pub duration: Duration,
+    pub elapsed: Duration,
+    pub intervals: Vec<Timer>,
+    // Finished while the app was hidden or asleep
+    pub missed: bool,
+}
+
+// A Rhai script that can generate the sequence with sequence() and react
+// to on_start(event) and on_finish(event). Scripts cannot import modules
+// or touch files, and every call is cut off after a bounded amount of work
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    // Bound to `this` in every call, so hooks can keep count between calls
+    state: Dynamic,
+    actions: Rc<RefCell<Vec<ScriptAction>>>,
+    output: Rc<RefCell<Vec<String>>>,
+}
+
+const MAX_OPERATIONS: u64 = 1_000_000;
+const MAX_SIZE: usize = 10_000;
+const MAX_OUTPUT_LINES: usize = 20;
+const DEFAULT_COLOR: (u8, u8, u8) = (0xe6, 0x4a, 0x19);
+
+impl Script {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source =
+            fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        Self::compile(&source)
+    }
+
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let actions = Rc::new(RefCell::new(vec![]));
+        let output = Rc::new(RefCell::new(vec![]));
+        let engine = sandboxed_engine(&actions, &output);
+
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        // Top level statements run once, e.g. to print a greeting
+        engine
+            .run_ast_with_scope(&mut Scope::new(), &ast)
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            engine,
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            actions,
+            output,
+        })
+    }
+
+    // None when the script leaves the sequence alone
+    pub fn generate(&mut self) -> Result<Option<Vec<Timer>>, String> {
+        if !self.has_function("sequence", 0) {
+            return Ok(None);
+        }
+
+        let result: Dynamic = self.call("sequence", ())?;
+        let intervals = result
+            .into_typed_array::<Map>()
+            .map_err(|_| "sequence() should return an array of intervals".to_string())?;
+        intervals
+            .iter()
+            .map(map_to_timer)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
+    pub fn on_start(&mut self, event: &ScriptEvent) -> Result<Vec<ScriptAction>, String> {
+        self.hook("on_start", event)
+    }
+
+    pub fn on_finish(&mut self, event: &ScriptEvent) -> Result<Vec<ScriptAction>, String> {
+        self.hook("on_finish", event)
+    }
+
+    // Lines printed by the script, oldest first
+    pub fn output(&self) -> Vec<String> {
+        self.output.borrow().clone()
+    }
+
+    fn hook(&mut self, name: &str, event: &ScriptEvent) -> Result<Vec<ScriptAction>, String> {
+        if !self.has_function(name, 1) {
+            return Ok(vec![]);
+        }
+
+        self.actions.borrow_mut().clear();
+        let _: Dynamic = self.call(name, (event_to_map(event),))?;
+        Ok(self.actions.borrow_mut().drain(..).collect())
+    }
+
+    fn call(&mut self, name: &str, args: impl rhai::FuncArgs) -> Result<Dynamic, String> {
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        self.engine
+            .call_fn_with_options(options, &mut Scope::new(), &self.ast, name, args)
+            .map_err(|e| format!("{}(): {}", name, e))
+    }
+
+    fn has_function(&self, name: &str, params: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == params)
+    }
+}
+
+fn sandboxed_engine(
+    actions: &Rc<RefCell<Vec<ScriptAction>>>,
+    output: &Rc<RefCell<Vec<String>>>,
+) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(MAX_SIZE);
+    engine.set_max_array_size(MAX_SIZE);
+    engine.set_max_map_size(MAX_SIZE);
+
+    let lines = output.clone();
+    engine.on_print(move |text| {
+        let mut lines = lines.borrow_mut();
+        if lines.len() == MAX_OUTPUT_LINES {
+            lines.remove(0);
+        }
+        lines.push(text.to_string());
+    });
+
+    let queue = actions.clone();
+    engine.register_fn("skip", move || queue.borrow_mut().push(ScriptAction::Skip));
+    let queue = actions.clone();
+    engine.register_fn("pause", move || {
+        queue.borrow_mut().push(ScriptAction::Pause)
+    });
+    let queue = actions.clone();
+    engine.register_fn("extend", move |secs: INT| {
+        queue
+            .borrow_mut()
+            .push(ScriptAction::Extend(secs.saturating_mul(1000)));
+    });
+    let queue = actions.clone();
+    engine.register_fn("extend", move |secs: FLOAT| {
+        queue
+            .borrow_mut()
+            .push(ScriptAction::Extend((secs * 1000.0) as i64));
+    });
+    let queue = actions.clone();
+    engine.register_fn(
+        "insert",
+        move |interval: Map| -> Result<(), Box<rhai::EvalAltResult>> {
+            let timer = map_to_timer(&interval)?;
+            queue.borrow_mut().push(ScriptAction::Insert(timer));
+            Ok(())
+        },
+    );
+
+    let rng = Rc::new(RefCell::new(Rng::from_time()));
+    let random = rng.clone();
+    engine.register_fn("random", move |min: INT, max: INT| -> INT {
+        let (min, max) = (min.min(max), min.max(max));
+        let span = max.abs_diff(min);
+        min.saturating_add_unsigned(random.borrow_mut().range(0, span))
+    });
+    let random = rng.clone();
+    engine.register_fn("pick", move |items: Array| -> Dynamic {
+        match items.len() {
+            0 => Dynamic::UNIT,
+            n => items[random.borrow_mut().range(0, n as u64 - 1) as usize].clone(),
+        }
+    });
+    let random = rng;
+    engine.register_fn("shuffle", move |mut items: Array| -> Array {
+        random.borrow_mut().shuffle(&mut items);
+        items
+    });
+
+    engine
+}
+
+// e.g. #{ secs: 30, label: "Squats", color: "#e64a19" } or #{ kind: "open", advance: "hold" }
+fn map_to_timer(map: &Map) -> Result<Timer, String> {
+    let number = |key: &str| -> Result<Option<f64>, String> {
+        match map.get(key) {
+            None => Ok(None),
+            Some(value) => value
+                .as_int()
+                .map(|v| v as f64)
+                .or_else(|_| value.as_float())
+                .map(Some)
+                .map_err(|_| format!("Expected '{}' to be a number", key)),
+        }
+    };
+    let text = |key: &str| -> Result<Option<String>, String> {
+        match map.get(key) {
+            None => Ok(None),
+            Some(value) => value
+                .clone()
+                .into_string()
+                .map(Some)
+                .map_err(|_| format!("Expected '{}' to be a string", key)),
+        }
+    };
+
+    let kind = match text("kind")?.as_deref() {
+        None | Some("countdown") => TimerKind::Countdown,
+        Some(kind) => kind.parse::<TimerKind>()?,
+    };
+    let millis = match (number("ms")?, number("secs")?) {
+        (Some(ms), _) => ms,
+        (None, Some(secs)) => secs * 1000.0,
+        (None, None) if kind != TimerKind::Countdown => 0.0,
+        (None, None) => return Err("An interval needs 'secs' or 'ms'".to_string()),
+    };
+    if millis < 0.0 {
+        return Err("Intervals cannot be negative".to_string());
+    }
+    let advance = match text("advance")? {
+        Some(advance) => advance.parse::<Advance>()?,
+        None => Advance::Auto,
+    };
+    let color = match text("color")? {
+        Some(color) => parse_color(color.trim_start_matches('#'))?,
+        None => DEFAULT_COLOR,
+    };
+
+    Ok(Timer {
+        millis: millis as u64,
+        color,
+        kind,
+        label: sanitize_label(&text("label")?.unwrap_or_default()),
+        advance,
+    })
+}
+
+fn timer_to_map(timer: &Timer) -> Map {
+    let (r, g, b) = timer.color;
+    let mut map = Map::new();
+    map.insert("secs".into(), (timer.millis as FLOAT / 1000.0).into());
+    map.insert("ms".into(), (timer.millis as INT).into());
+    map.insert("label".into(), timer.label.clone().into());
+    map.insert(
+        "color".into(),
+        format!("#{:02x}{:02x}{:02x}", r, g, b).into(),
+    );
+    map.insert("kind".into(), timer.kind.to_string().into());
+    map.insert("advance".into(), timer.advance.to_string().into());
+    map
+}
+
+fn event_to_map(event: &ScriptEvent) -> Map {
+    let intervals: Array = event
+        .intervals
+        .iter()
+        .map(|timer| Dynamic::from_map(timer_to_map(timer)))
+        .collect();
+
+    let mut map = Map::new();
+    map.insert("index".into(), (event.index as INT).into());
+    map.insert("round".into(), (event.round as INT).into());
+    map.insert("count".into(), (event.intervals.len() as INT).into());
+    map.insert("label".into(), event.label.clone().into());
+    map.insert("secs".into(), event.duration.as_secs_f64().into());
+    map.insert("elapsed_secs".into(), event.elapsed.as_secs_f64().into());
+    map.insert("intervals".into(), intervals.into());
+    map.insert("missed".into(), event.missed.into());
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(source: &str) -> Result<Vec<Timer>, String> {
+        Script::compile(source)?
+            .generate()
+            .map(|timers| timers.unwrap_or_default())
+    }
+
+    fn interval(fields: &str) -> Result<Timer, String> {
+        let mut timers = generate(&format!("fn sequence() {{ [#{{ {} }}] }}", fields))?;
+        Ok(timers.remove(0))
+    }
+
+    #[test]
+    fn maps_intervals_to_timers() {
+        let timer = interval(r##"secs: 30, label: "Squats", color: "#102030""##).unwrap();
+        assert_eq!(timer.millis, 30_000);
+        assert_eq!(timer.label, "Squats");
+        assert_eq!(timer.color, (0x10, 0x20, 0x30));
+        assert_eq!(timer.kind, TimerKind::Countdown);
+        assert_eq!(timer.advance, Advance::Auto);
+
+        // ms wins over secs, fractions are fine
+        assert_eq!(interval("ms: 1500, secs: 9").unwrap().millis, 1500);
+        assert_eq!(interval("secs: 2.5").unwrap().millis, 2500);
+        assert_eq!(interval("secs: 1").unwrap().color, DEFAULT_COLOR);
+
+        let open = interval(r#"kind: "open", advance: "hold""#).unwrap();
+        assert_eq!(open.kind, TimerKind::OpenEnded);
+        assert_eq!(open.advance, Advance::Hold);
+        assert_eq!(open.millis, 0);
+    }
+
+    #[test]
+    fn rejects_bad_intervals() {
+        assert!(interval(r#"label: "No length""#).is_err());
+        assert!(interval("secs: -1").is_err());
+        assert!(interval(r#"secs: "ten""#).is_err());
+        assert!(interval(r#"secs: 1, kind: "sideways""#).is_err());
+        assert!(interval(r#"secs: 1, advance: "never""#).is_err());
+        assert!(interval(r#"secs: 1, color: "blue""#).is_err());
+        assert!(generate("fn sequence() { 5 }").is_err());
+    }
+
+    #[test]
+    fn leaves_the_sequence_alone_without_sequence_fn() {
+        let mut script = Script::compile("fn on_start(event) { skip(); }").unwrap();
+        assert_eq!(script.generate(), Ok(None));
+    }
+
+    #[test]
+    fn cuts_off_runaway_scripts() {
+        assert!(Script::compile("loop {}").is_err());
+        assert!(generate("fn sequence() { sequence() }").is_err());
+        assert!(Script::compile(r#"let s = "x"; loop { s += s; }"#).is_err());
+        assert!(Script::compile("let a = []; loop { a.push(1); }").is_err());
+    }
+
+    #[test]
+    fn cannot_reach_outside_the_sandbox() {
+        assert!(Script::compile(r#"import "other" as other;"#).is_err());
+        assert!(Script::compile(r#"eval("1 + 1")"#).is_err());
+    }
+
+    #[test]
+    fn hooks_queue_actions_and_keep_state() {
+        let mut script = Script::compile(
+            r#"
+            fn on_finish(event) {
+                this.count = (this.count ?? 0) + 1;
+                if this.count == 2 { pause(); }
+                extend(event.secs);
+            }
+            "#,
+        )
+        .unwrap();
+        let event = ScriptEvent {
+            index: 0,
+            round: 0,
+            label: String::new(),
+            duration: Duration::from_secs(3),
+            elapsed: Duration::from_secs(3),
+            intervals: vec![],
+            missed: false,
+        };
+
+        assert_eq!(
+            script.on_finish(&event),
+            Ok(vec![ScriptAction::Extend(3000)])
+        );
+        assert_eq!(
+            script.on_finish(&event),
+            Ok(vec![ScriptAction::Pause, ScriptAction::Extend(3000)])
+        );
+        assert_eq!(script.on_start(&event), Ok(vec![]));
+    }
+}
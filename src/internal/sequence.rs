@@ -0,0 +1,516 @@
+use std::fmt;
+
+use crate::internal::settings_parser::parse_color;
+use crate::internal::timer::{Advance, Timer, TimerKind, sanitize_label};
+
+const DEFAULT_COLOR: (u8, u8, u8) = (255, 0, 0);
+
+// Keep "99999999x(99999999x(1s))" from hanging the expansion
+const MAX_REPEAT: u32 = 1000;
+const MAX_DEPTH: usize = 8;
+const MAX_INTERVALS: u64 = 10_000;
+
+const NAMED_COLORS: [(&str, (u8, u8, u8)); 17] = [
+    ("red", (255, 0, 0)),
+    ("green", (0, 255, 0)),
+    ("blue", (0, 0, 255)),
+    ("yellow", (255, 255, 0)),
+    ("orange", (255, 165, 0)),
+    ("purple", (128, 0, 128)),
+    ("pink", (255, 192, 203)),
+    ("cyan", (0, 255, 255)),
+    ("magenta", (255, 0, 255)),
+    ("white", (255, 255, 255)),
+    ("black", (0, 0, 0)),
+    ("gray", (128, 128, 128)),
+    ("grey", (128, 128, 128)),
+    ("teal", (0, 128, 128)),
+    ("lime", (50, 205, 50)),
+    ("brown", (165, 42, 42)),
+    ("navy", (0, 0, 128)),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Item {
+    Interval(Timer),
+    Repeat(u32, Vec<Item>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequenceError {
+    pub position: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TokenKind {
+    Word(String),
+    Quoted(String),
+    Hex(String),
+    Open,
+    Close,
+    Separator,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    end: usize,
+    depth: usize,
+}
+
+pub fn parse_sequence(text: &str) -> Result<Vec<Item>, SequenceError> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens,
+        index: 0,
+        end: text.chars().count() + 1,
+        depth: 0,
+    };
+
+    let items = parser.parse_list()?;
+    if let Some(token) = parser.peek() {
+        return Err(error(token.position, "Unexpected ')'"));
+    }
+    if expanded_len(&items) > MAX_INTERVALS {
+        return Err(error(1, &too_long()));
+    }
+
+    Ok(items)
+}
+
+pub fn expand(items: &[Item]) -> Vec<Timer> {
+    items
+        .iter()
+        .flat_map(|item| match item {
+            Item::Interval(timer) => vec![timer.clone()],
+            Item::Repeat(count, items) => {
+                let block = expand(items);
+                (0..*count).flat_map(|_| block.clone()).collect()
+            }
+        })
+        .collect()
+}
+
+pub fn format_sequence(items: &[Item]) -> String {
+    format_list(items, "; ")
+}
+
+pub fn timers_to_sequence(timers: &[Timer]) -> String {
+    let items: Vec<Item> = timers.iter().cloned().map(Item::Interval).collect();
+    format_sequence(&items)
+}
+
+pub fn named_color(name: &str) -> Option<(u8, u8, u8)> {
+    let name = name.to_lowercase();
+    NAMED_COLORS
+        .iter()
+        .find(|(color_name, _)| *color_name == name)
+        .map(|(_, color)| *color)
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn position(&self) -> usize {
+        self.peek().map(|token| token.position).unwrap_or(self.end)
+    }
+
+    fn parse_list(&mut self) -> Result<Vec<Item>, SequenceError> {
+        let mut items = vec![];
+
+        loop {
+            match self.peek().map(|token| &token.kind) {
+                None | Some(TokenKind::Close) => return Ok(items),
+                Some(TokenKind::Separator) => {
+                    self.index += 1;
+                    continue;
+                }
+                _ => items.push(self.parse_item()?),
+            }
+
+            match self.peek().map(|token| &token.kind) {
+                None | Some(TokenKind::Close) | Some(TokenKind::Separator) => {}
+                _ => return Err(error(self.position(), "Expected ';' or ','")),
+            }
+        }
+    }
+
+    fn parse_item(&mut self) -> Result<Item, SequenceError> {
+        if let Some(count) = self.repeat_count() {
+            let open_position = self.tokens[self.index + 1].position;
+            if self.depth == MAX_DEPTH {
+                let message = format!("Repeats can be nested at most {} deep", MAX_DEPTH);
+                return Err(error(open_position, &message));
+            }
+            self.index += 2;
+
+            self.depth += 1;
+            let items = self.parse_list()?;
+            self.depth -= 1;
+            match self.peek().map(|token| &token.kind) {
+                Some(TokenKind::Close) => self.index += 1,
+                _ => return Err(error(open_position, "Unclosed '('")),
+            }
+
+            if count == 0 {
+                return Err(error(open_position, "Repeat count must be at least 1"));
+            }
+            if count > MAX_REPEAT {
+                let message = format!("Repeat count must be at most {}", MAX_REPEAT);
+                return Err(error(open_position, &message));
+            }
+
+            let item = Item::Repeat(count, items);
+            if expanded_len(std::slice::from_ref(&item)) > MAX_INTERVALS {
+                return Err(error(open_position, &too_long()));
+            }
+            return Ok(item);
+        }
+
+        self.parse_interval().map(Item::Interval)
+    }
+
+    fn repeat_count(&self) -> Option<u32> {
+        let Some(TokenKind::Word(word)) = self.peek().map(|token| &token.kind) else {
+            return None;
+        };
+        let next = self.tokens.get(self.index + 1).map(|token| &token.kind);
+        if next != Some(&TokenKind::Open) {
+            return None;
+        }
+
+        parse_repeat(word)
+    }
+
+    fn parse_interval(&mut self) -> Result<Timer, SequenceError> {
+        let mut label_words: Vec<String> = vec![];
+
+        let (millis, kind) = loop {
+            let position = self.position();
+            match self.peek().map(|token| token.kind.clone()) {
+                Some(TokenKind::Word(word)) => {
+                    self.index += 1;
+                    if let Ok(kind) = word.parse::<TimerKind>() {
+                        break (0, kind);
+                    }
+                    if let Some(millis) = parse_duration(&word) {
+                        break (millis, TimerKind::Countdown);
+                    }
+                    label_words.push(word);
+                }
+                Some(TokenKind::Quoted(text)) => {
+                    self.index += 1;
+                    label_words.push(text);
+                }
+                _ => {
+                    return Err(error(position, "Expected a duration such as 20s or 1m30s"));
+                }
+            }
+        };
+
+        let position = self.position();
+        let color = match self.peek().map(|token| token.kind.clone()) {
+            Some(TokenKind::Hex(hex)) => {
+                self.index += 1;
+                parse_color(&hex).map_err(|e| error(position, &e))?
+            }
+            Some(TokenKind::Word(word)) if word.parse::<Advance>().is_err() => {
+                self.index += 1;
+                named_color(&word)
+                    .ok_or_else(|| error(position, &format!("Unknown color '{}'", word)))?
+            }
+            _ => DEFAULT_COLOR,
+        };
+
+        // e.g. "setup open hold" or "rest 1m green confirm"
+        let advance = match self.peek().map(|token| token.kind.clone()) {
+            Some(TokenKind::Word(word)) => match word.parse::<Advance>() {
+                Ok(advance) => {
+                    self.index += 1;
+                    advance
+                }
+                Err(_) => Advance::Auto,
+            },
+            _ => Advance::Auto,
+        };
+
+        Ok(Timer {
+            millis,
+            color,
+            kind,
+            label: sanitize_label(&label_words.join(" ")),
+            advance,
+        })
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, SequenceError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            ';' | ',' => TokenKind::Separator,
+            '#' => {
+                let hex: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphanumeric())
+                    .collect();
+                i += hex.chars().count();
+                TokenKind::Hex(hex)
+            }
+            '"' => {
+                let mut quoted = String::new();
+                let mut closed = false;
+                i += 1;
+                while i < chars.len() {
+                    match chars[i] {
+                        '\\' if i + 1 < chars.len() => {
+                            quoted.push(chars[i + 1]);
+                            i += 1;
+                        }
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        c => quoted.push(c),
+                    }
+                    i += 1;
+                }
+
+                if !closed {
+                    return Err(error(position, "Unclosed '\"'"));
+                }
+                TokenKind::Quoted(quoted)
+            }
+            c if is_word_char(c) => {
+                let word: String = chars[i..]
+                    .iter()
+                    .take_while(|c| is_word_char(**c))
+                    .collect();
+                i += word.chars().count() - 1;
+                TokenKind::Word(word)
+            }
+            c => return Err(error(position, &format!("Unexpected character '{}'", c))),
+        };
+
+        tokens.push(Token { kind, position });
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '\'' | '.')
+}
+
+fn parse_repeat(word: &str) -> Option<u32> {
+    word.strip_suffix(['x', 'X'])?.parse::<u32>().ok()
+}
+
+pub fn parse_duration(word: &str) -> Option<u64> {
+    let mut rest = word;
+    let mut total: u64 = 0;
+
+    while !rest.is_empty() {
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        let value = rest[..digits].parse::<u64>().ok()?;
+        rest = &rest[digits..];
+
+        let (unit, factor) = if rest.starts_with("ms") {
+            ("ms", 1)
+        } else if rest.starts_with('h') {
+            ("h", 3_600_000)
+        } else if rest.starts_with('m') {
+            ("m", 60_000)
+        } else if rest.starts_with('s') {
+            ("s", 1000)
+        } else {
+            return None;
+        };
+
+        total = total.checked_add(value.checked_mul(factor)?)?;
+        rest = &rest[unit.len()..];
+    }
+
+    match word.is_empty() {
+        true => None,
+        false => Some(total),
+    }
+}
+
+pub fn format_duration(millis: u64) -> String {
+    if millis == 0 {
+        return "0s".to_string();
+    }
+
+    let parts = [
+        (millis / 3_600_000, "h"),
+        ((millis / 60_000) % 60, "m"),
+        ((millis / 1000) % 60, "s"),
+        (millis % 1000, "ms"),
+    ];
+
+    parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect()
+}
+
+fn format_list(items: &[Item], separator: &str) -> String {
+    items
+        .iter()
+        .map(|item| match item {
+            Item::Interval(timer) => format_interval(timer),
+            Item::Repeat(count, items) => format!("{}x({})", count, format_list(items, ", ")),
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn format_interval(timer: &Timer) -> String {
+    let time = match timer.kind {
+        TimerKind::Countdown => format_duration(timer.millis),
+        kind => kind.to_string(),
+    };
+
+    let color = NAMED_COLORS
+        .iter()
+        .find(|(_, color)| *color == timer.color)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| {
+            let (r, g, b) = timer.color;
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        });
+
+    let interval = match timer.label.is_empty() {
+        true => format!("{} {}", time, color),
+        false => format!("{} {} {}", format_label(&timer.label), time, color),
+    };
+    match timer.advance {
+        Advance::Auto => interval,
+        advance => format!("{} {}", interval, advance),
+    }
+}
+
+fn format_label(label: &str) -> String {
+    let words: Vec<&str> = label.split(' ').collect();
+    let bare = words.iter().all(|word| {
+        !word.is_empty()
+            && word.chars().all(is_word_char)
+            && parse_duration(word).is_none()
+            && word.parse::<TimerKind>().is_err()
+            && parse_repeat(word).is_none()
+    });
+
+    match bare {
+        true => label.to_string(),
+        false => format!("\"{}\"", label.replace('\\', "\\\\").replace('"', "\\\"")),
+    }
+}
+
+fn expanded_len(items: &[Item]) -> u64 {
+    items.iter().fold(0u64, |total, item| {
+        let len = match item {
+            Item::Interval(_) => 1,
+            Item::Repeat(count, items) => expanded_len(items).saturating_mul(*count as u64),
+        };
+        total.saturating_add(len)
+    })
+}
+
+fn too_long() -> String {
+    format!("Sequence expands to more than {} intervals", MAX_INTERVALS)
+}
+
+fn error(position: usize, message: &str) -> SequenceError {
+    SequenceError {
+        position,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(text: &str) -> String {
+        let items = parse_sequence(text).unwrap();
+        let formatted = format_sequence(&items);
+        assert_eq!(parse_sequence(&formatted).unwrap(), items);
+        formatted
+    }
+
+    #[test]
+    fn formats_what_it_parses() {
+        assert_eq!(round_trip("warm up 5m orange"), "warm up 5m orange");
+        assert_eq!(
+            round_trip("3x(work 20s #123456, rest 10s green confirm); setup stopwatch hold"),
+            "3x(work 20s #123456, rest 10s green confirm); setup stopwatch red hold"
+        );
+        assert_eq!(round_trip("\"2x\" 1m30s blue"), "\"2x\" 1m30s blue");
+        assert_eq!(round_trip("2x(2x(1s), 1h)"), "2x(2x(1s red), 1h red)");
+    }
+
+    #[test]
+    fn expands_repeats() {
+        let items = parse_sequence("2x(a 1s, 2x(b 2s))").unwrap();
+        let labels: Vec<String> = expand(&items).into_iter().map(|t| t.label).collect();
+        assert_eq!(labels, ["a", "b", "b", "a", "b", "b"]);
+    }
+
+    #[test]
+    fn rejects_large_repeat_counts() {
+        let error = parse_sequence("1001x(1s)").unwrap_err();
+        assert_eq!(error.position, 6);
+        assert!(parse_sequence("1000x(1s)").is_ok());
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let nested = |depth: usize| format!("{}1s{}", "1x(".repeat(depth), ")".repeat(depth));
+        assert!(parse_sequence(&nested(MAX_DEPTH)).is_ok());
+        assert!(parse_sequence(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(parse_sequence(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn rejects_long_expansions() {
+        assert!(parse_sequence("99999999x(99999999x(1s))").is_err());
+        assert!(parse_sequence("1000x(1000x(1s))").is_err());
+        assert!(parse_sequence("1000x(10x(1s))").is_ok());
+        assert!(parse_sequence("1000x(10x(1s)); 1s").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::io;
+use std::time::Duration;
+
+use crate::internal::keys_and_values::{KeysAndValues, ParseError};
+use crate::internal::settings_parser::{parse_timers, timers_to_string};
+use crate::internal::timer::Timer;
+
+#[derive(Clone, Debug)]
+pub struct SessionState {
+    pub profile: String,
+    pub timers: Vec<Timer>,
+    // The shuffled round that was playing, in the order it was dealt
+    pub dealt: Option<Vec<Timer>>,
+    pub index: usize,
+    pub round: u32,
+    pub elapsed: Duration,
+    pub paused: bool,
+}
+
+impl SessionState {
+    pub fn new_from_file(path: &str) -> Result<Option<Self>, String> {
+        let file_content = match fs::read_to_string(path) {
+            Ok(value) => value,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Unhandled io error: {}", e)),
+        };
+
+        let kv = KeysAndValues::new_from_str(&file_content).map_err(|e| e.to_string())?;
+
+        let profile = kv
+            .get("profile", |v| Ok::<_, &str>(v.to_string()))
+            .unwrap_or_default();
+        let timers = kv.get("timers", parse_timers).map_err(|e| e.to_string())?;
+        let dealt = match kv.get("dealt", parse_timers) {
+            Ok(v) => Some(v),
+            Err(ParseError::MissingKey(_)) => None,
+            Err(e) => return Err(e.to_string()),
+        };
+        let index = kv
+            .get("index", |v| v.parse::<usize>())
+            .map_err(|e| e.to_string())?;
+        let round = kv
+            .get("round", |v| v.parse::<u32>())
+            .map_err(|e| e.to_string())?;
+        let elapsed = kv
+            .get("elapsed", |v| v.parse::<u64>().map(Duration::from_millis))
+            .map_err(|e| e.to_string())?;
+        let paused = kv
+            .get("paused", |v| v.parse::<bool>())
+            .map_err(|e| e.to_string())?;
+
+        Ok(Some(Self {
+            profile,
+            timers,
+            dealt,
+            index,
+            round,
+            elapsed,
+            paused,
+        }))
+    }
+
+    pub fn write_to_file(&self, path: &str) -> Result<(), String> {
+        let mut kv = KeysAndValues::new();
+
+        kv.set("profile", &self.profile, |v| v.to_string());
+        kv.set("timers", &self.timers, |v| timers_to_string(v));
+        if let Some(dealt) = &self.dealt {
+            kv.set("dealt", dealt, |v| timers_to_string(v));
+        }
+        kv.set("index", &self.index, |v| v.to_string());
+        kv.set("round", &self.round, |v| v.to_string());
+        kv.set("elapsed", &self.elapsed, |v| v.as_millis().to_string());
+        kv.set("paused", &self.paused, |v| v.to_string());
+
+        // Written next to the real file first so that a crash
+        // mid-write never leaves a truncated session behind
+        let temp_path = format!("{}.tmp", path);
+        kv.write_to_file(&temp_path).map_err(|v| v.to_string())?;
+        fs::rename(&temp_path, path).map_err(|v| v.to_string())
+    }
+
+    pub fn remove_file(path: &str) -> Result<(), String> {
+        match fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Unhandled io error: {}", e)),
+        }
+    }
+
+    pub fn has_progress(&self) -> bool {
+        self.index > 0 || self.round > 0 || !self.elapsed.is_zero()
+    }
+}
@@ -1,23 +1,290 @@
+use std::fmt;
 use std::fs;
 use std::io;
+use std::str::FromStr;
 
+use crate::internal::keybindings::{KeyBindings, keybindings_to_string, parse_keybindings};
 use crate::internal::keys_and_values::{KeysAndValues, ParseError};
+use crate::internal::sequence::{expand, parse_sequence};
+use crate::internal::theme::{Palette, Theme, palettes_to_string, parse_palettes};
+use crate::internal::timer::{Advance, Timer, TimerKind, sanitize_label};
+use crate::internal::title::DEFAULT_TITLE_FORMAT;
 
 #[derive(Default, Clone, Debug)]
 pub struct AppSettings {
-    pub timers: Vec<(u64, (u8, u8, u8))>,
+    pub timers: Vec<Timer>,
     pub tick_interval: u64,
     pub play_once: bool,
+    pub keep_adjustments: bool,
     pub volume: f32,
     pub use_custom_sound: bool,
     pub custom_sound_location: String,
+    pub catch_up: CatchUp,
+    pub debug_overlay: bool,
+    pub profile: String,
+    pub sequence: String,
+    pub keybindings: KeyBindings,
+    pub mini_window: Option<WindowGeometry>,
+    pub mini_click_through: bool,
+    pub progress_style: ProgressStyle,
+    pub ring_thickness: f32,
+    pub session_panel: bool,
+    pub effects: EffectSettings,
+    pub theme: Theme,
+    pub font_size: f32,
+    pub timer_font_scale: f32,
+    pub palettes: Vec<Palette>,
+    pub title_format: String,
+    pub notifications: bool,
+    pub notify_before_seconds: u64,
+    pub notifications_bus: String,
+    pub mpris: bool,
+    pub mpris_bus: String,
+    pub osc: bool,
+    pub osc_target: String,
+    pub osc_bind: String,
+    pub osc_listen_port: u16,
+    pub osc_remaining_ms: u64,
+    pub mqtt: MqttSettings,
+    pub script: String,
+    pub shuffle: ShuffleSettings,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EffectSettings {
+    pub flash: bool,
+    pub pulse_seconds: u64,
+    pub animate: bool,
+    pub reduce_motion: bool,
+    pub intensity: f32,
+    pub duration_ms: u64,
+}
+
+impl Default for EffectSettings {
+    fn default() -> Self {
+        Self {
+            flash: false,
+            pulse_seconds: 0,
+            animate: false,
+            reduce_motion: false,
+            intensity: 0.5,
+            duration_ms: 600,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub broker: String,
+    pub topic: String,
+    pub username: String,
+    pub password: String,
+    pub discovery: bool,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            broker: "127.0.0.1:1883".to_string(),
+            topic: "interval-timer".to_string(),
+            username: String::new(),
+            password: String::new(),
+            discovery: true,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShuffleSettings {
+    // Reorder the intervals every round
+    pub shuffle: bool,
+    // Play this many of the intervals each round, 0 plays them all
+    pub pick: usize,
+    // Countdowns last a random whole number of seconds in this range,
+    // a max of 0 keeps their own durations
+    pub min_secs: u64,
+    pub max_secs: u64,
+    // Without a seed every run is different
+    pub seed: Option<u64>,
+}
+
+impl ShuffleSettings {
+    pub fn is_active(&self) -> bool {
+        self.shuffle || self.pick > 0 || self.max_secs > 0
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressStyle {
+    #[default]
+    Pie,
+    Ring,
+    Bar,
+    Digital,
+    Hourglass,
+    Session,
+}
+
+impl ProgressStyle {
+    pub const ALL: [ProgressStyle; 6] = [
+        ProgressStyle::Pie,
+        ProgressStyle::Ring,
+        ProgressStyle::Bar,
+        ProgressStyle::Digital,
+        ProgressStyle::Hourglass,
+        ProgressStyle::Session,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProgressStyle::Pie => "Pie",
+            ProgressStyle::Ring => "Ring",
+            ProgressStyle::Bar => "Bar",
+            ProgressStyle::Digital => "Digital",
+            ProgressStyle::Hourglass => "Hourglass",
+            ProgressStyle::Session => "Whole session",
+        }
+    }
+}
+
+impl FromStr for ProgressStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        ProgressStyle::ALL
+            .into_iter()
+            .find(|style| style.to_string() == value)
+            .ok_or_else(|| {
+                "Expected one of pie, ring, bar, digital, hourglass or session".to_string()
+            })
+    }
+}
+
+impl fmt::Display for ProgressStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProgressStyle::Pie => write!(f, "pie"),
+            ProgressStyle::Ring => write!(f, "ring"),
+            ProgressStyle::Bar => write!(f, "bar"),
+            ProgressStyle::Digital => write!(f, "digital"),
+            ProgressStyle::Hourglass => write!(f, "hourglass"),
+            ProgressStyle::Session => write!(f, "session"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowGeometry {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl FromStr for WindowGeometry {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parts = value
+            .split(',')
+            .map(|v| v.trim().parse::<f32>().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match parts[..] {
+            [x, y, width, height] => Ok(Self {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => Err("Expected x,y,width,height".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for WindowGeometry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CatchUp {
+    SkipMissedChimes,
+    #[default]
+    PlayOneChime,
+    PauseOnResume,
+}
+
+impl CatchUp {
+    pub const ALL: [CatchUp; 3] = [
+        CatchUp::SkipMissedChimes,
+        CatchUp::PlayOneChime,
+        CatchUp::PauseOnResume,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CatchUp::SkipMissedChimes => "Skip missed chimes",
+            CatchUp::PlayOneChime => "Play one chime",
+            CatchUp::PauseOnResume => "Pause on resume",
+        }
+    }
+}
+
+impl FromStr for CatchUp {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "skip" => Ok(CatchUp::SkipMissedChimes),
+            "chime" => Ok(CatchUp::PlayOneChime),
+            "pause" => Ok(CatchUp::PauseOnResume),
+            _ => Err("Expected one of skip, chime or pause".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for CatchUp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatchUp::SkipMissedChimes => write!(f, "skip"),
+            CatchUp::PlayOneChime => write!(f, "chime"),
+            CatchUp::PauseOnResume => write!(f, "pause"),
+        }
+    }
 }
 
 static DEFAULT_TICK_INTERVAL: u64 = 50;
 static DEFAULT_PLAY_ONCE: bool = false;
+static DEFAULT_KEEP_ADJUSTMENTS: bool = false;
 static DEFAULT_VOLUME: f32 = 0.5;
 static DEFAULT_USE_CUSTOM_SOUND: bool = false;
 static DEFAULT_CUSTOM_SOUND_LOCATION: String = String::new();
+static DEFAULT_CATCH_UP: CatchUp = CatchUp::PlayOneChime;
+static DEFAULT_DEBUG_OVERLAY: bool = false;
+static DEFAULT_PROFILE: String = String::new();
+static DEFAULT_SEQUENCE: String = String::new();
+static DEFAULT_MINI_CLICK_THROUGH: bool = false;
+static DEFAULT_PROGRESS_STYLE: ProgressStyle = ProgressStyle::Pie;
+static DEFAULT_RING_THICKNESS: f32 = 0.25;
+static DEFAULT_SESSION_PANEL: bool = false;
+static DEFAULT_NOTIFICATIONS: bool = false;
+static DEFAULT_NOTIFY_BEFORE_SECONDS: u64 = 0;
+static DEFAULT_NOTIFICATIONS_BUS: String = String::new();
+static DEFAULT_MPRIS: bool = true;
+static DEFAULT_MPRIS_BUS: String = String::new();
+static DEFAULT_OSC: bool = false;
+static DEFAULT_OSC_TARGET: &str = "127.0.0.1:9000";
+static DEFAULT_OSC_BIND: &str = "127.0.0.1";
+static DEFAULT_OSC_LISTEN_PORT: u16 = 9001;
+static DEFAULT_OSC_REMAINING_MS: u64 = 1000;
+static DEFAULT_SCRIPT: &str = "";
+static DEFAULT_THEME: Theme = Theme::Dark;
+static DEFAULT_FONT_SIZE: f32 = 14.0;
+static DEFAULT_TIMER_FONT_SCALE: f32 = 1.0;
 
 impl AppSettings {
     pub fn new_from_file(path: &str) -> Result<Self, String> {
@@ -32,7 +299,17 @@ impl AppSettings {
     pub fn new_from_str(data: String) -> Result<Self, String> {
         let kv = KeysAndValues::new_from_str(&data).map_err(|e| e.to_string())?;
 
+        let sequence = match kv.get("sequence", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_SEQUENCE.clone(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        // A sequence takes precedence over the plain timer list
         let timers = match kv.get("timers", |v| parse_timers(v)) {
+            _ if !sequence.is_empty() => parse_sequence(&sequence)
+                .map(|items| expand(&items))
+                .map_err(|e| format!("Invalid sequence: {}", e))?,
             Ok(value) => value,
             Err(ParseError::MissingKey(_)) => vec![],
             Err(e) => return Err(e.to_string()),
@@ -50,6 +327,12 @@ impl AppSettings {
             Err(e) => return Err(e.to_string()),
         };
 
+        let keep_adjustments = match kv.get("keep_adjustments", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_KEEP_ADJUSTMENTS,
+            Err(e) => return Err(e.to_string()),
+        };
+
         let volume = match kv.get("volume", |v| v.parse::<f32>()) {
             Ok(value) => value,
             Err(ParseError::MissingKey(_)) => DEFAULT_VOLUME,
@@ -69,13 +352,328 @@ impl AppSettings {
                 Err(e) => return Err(e.to_string()),
             };
 
+        let catch_up = match kv.get("catch_up", |v| v.parse::<CatchUp>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_CATCH_UP,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let debug_overlay = match kv.get("debug_overlay", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_DEBUG_OVERLAY,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let profile = match kv.get("profile", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_PROFILE.clone(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let keybindings = match kv.get("keybindings", parse_keybindings) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => KeyBindings::default(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mini_window = match kv.get("mini_window", |v| v.parse::<WindowGeometry>()) {
+            Ok(value) => Some(value),
+            Err(ParseError::MissingKey(_)) => None,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mini_click_through = match kv.get("mini_click_through", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_MINI_CLICK_THROUGH,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let progress_style = match kv.get("progress_style", |v| v.parse::<ProgressStyle>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_PROGRESS_STYLE,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let ring_thickness = match kv.get("ring_thickness", |v| v.parse::<f32>()) {
+            Ok(value) => value.clamp(0.05, 1.0),
+            Err(ParseError::MissingKey(_)) => DEFAULT_RING_THICKNESS,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let session_panel = match kv.get("session_panel", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_SESSION_PANEL,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let default_effects = EffectSettings::default();
+
+        let effect_flash = match kv.get("effect_flash", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_effects.flash,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let effect_pulse_seconds = match kv.get("effect_pulse_seconds", |v| v.parse::<u64>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_effects.pulse_seconds,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let effect_animate = match kv.get("effect_animate", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_effects.animate,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let reduce_motion = match kv.get("reduce_motion", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_effects.reduce_motion,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let effect_intensity = match kv.get("effect_intensity", |v| v.parse::<f32>()) {
+            Ok(value) => value.clamp(0.0, 1.0),
+            Err(ParseError::MissingKey(_)) => default_effects.intensity,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let effect_duration = match kv.get("effect_duration", |v| v.parse::<u64>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_effects.duration_ms,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let effects = EffectSettings {
+            flash: effect_flash,
+            pulse_seconds: effect_pulse_seconds,
+            animate: effect_animate,
+            reduce_motion,
+            intensity: effect_intensity,
+            duration_ms: effect_duration,
+        };
+
+        let theme = match kv.get("theme", |v| v.parse::<Theme>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_THEME,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let font_size = match kv.get("font_size", |v| v.parse::<f32>()) {
+            Ok(value) => value.clamp(8.0, 32.0),
+            Err(ParseError::MissingKey(_)) => DEFAULT_FONT_SIZE,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let timer_font_scale = match kv.get("timer_font_scale", |v| v.parse::<f32>()) {
+            Ok(value) => value.clamp(0.5, 3.0),
+            Err(ParseError::MissingKey(_)) => DEFAULT_TIMER_FONT_SCALE,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let palettes = match kv.get("palettes", parse_palettes) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => vec![],
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let title_format = match kv.get("title_format", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_TITLE_FORMAT.to_string(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let notifications = match kv.get("notifications", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_NOTIFICATIONS,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let notify_before_seconds = match kv.get("notify_before_seconds", |v| v.parse::<u64>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_NOTIFY_BEFORE_SECONDS,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let notifications_bus = match kv.get("notifications_bus", |v| Ok::<_, &str>(v.to_string()))
+        {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_NOTIFICATIONS_BUS.clone(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mpris = match kv.get("mpris", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_MPRIS,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mpris_bus = match kv.get("mpris_bus", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_MPRIS_BUS.clone(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let osc = match kv.get("osc", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_OSC,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let osc_target = match kv.get("osc_target", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_OSC_TARGET.to_string(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let osc_bind = match kv.get("osc_bind", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_OSC_BIND.to_string(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let osc_listen_port = match kv.get("osc_listen_port", |v| v.parse::<u16>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_OSC_LISTEN_PORT,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let osc_remaining_ms = match kv.get("osc_remaining_ms", |v| v.parse::<u64>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_OSC_REMAINING_MS,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let default_mqtt = MqttSettings::default();
+
+        let mqtt_enabled = match kv.get("mqtt", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_mqtt.enabled,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mqtt_broker = match kv.get("mqtt_broker", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_mqtt.broker,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mqtt_topic = match kv.get("mqtt_topic", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value.trim_end_matches('/').to_string(),
+            Err(ParseError::MissingKey(_)) => default_mqtt.topic,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mqtt_username = match kv.get("mqtt_username", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_mqtt.username,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mqtt_password = match kv.get("mqtt_password", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_mqtt.password,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mqtt_discovery = match kv.get("mqtt_discovery", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_mqtt.discovery,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mqtt = MqttSettings {
+            enabled: mqtt_enabled,
+            broker: mqtt_broker,
+            topic: mqtt_topic,
+            username: mqtt_username,
+            password: mqtt_password,
+            discovery: mqtt_discovery,
+        };
+
+        let script = match kv.get("script", |v| Ok::<_, &str>(v.to_string())) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => DEFAULT_SCRIPT.to_string(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let default_shuffle = ShuffleSettings::default();
+
+        let shuffle = match kv.get("shuffle", |v| v.parse::<bool>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_shuffle.shuffle,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let shuffle_pick = match kv.get("shuffle_pick", |v| v.parse::<usize>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_shuffle.pick,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let shuffle_min_secs = match kv.get("shuffle_min_secs", |v| v.parse::<u64>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_shuffle.min_secs,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let shuffle_max_secs = match kv.get("shuffle_max_secs", |v| v.parse::<u64>()) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_shuffle.max_secs,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let shuffle_seed = match kv.get("shuffle_seed", parse_seed) {
+            Ok(value) => value,
+            Err(ParseError::MissingKey(_)) => default_shuffle.seed,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let shuffle = ShuffleSettings {
+            shuffle,
+            pick: shuffle_pick,
+            min_secs: shuffle_min_secs,
+            max_secs: shuffle_max_secs,
+            seed: shuffle_seed,
+        };
+
         Ok(Self {
             timers,
             tick_interval,
             play_once,
+            keep_adjustments,
             volume,
             use_custom_sound,
             custom_sound_location,
+            catch_up,
+            debug_overlay,
+            profile,
+            sequence,
+            keybindings,
+            mini_window,
+            mini_click_through,
+            progress_style,
+            ring_thickness,
+            session_panel,
+            effects,
+            theme,
+            font_size,
+            timer_font_scale,
+            palettes,
+            title_format,
+            notifications,
+            notify_before_seconds,
+            notifications_bus,
+            mpris,
+            mpris_bus,
+            osc,
+            osc_target,
+            osc_bind,
+            osc_listen_port,
+            osc_remaining_ms,
+            mqtt,
+            script,
+            shuffle,
         })
     }
 
@@ -84,12 +682,35 @@ impl AppSettings {
         kv.write_to_file(path).map_err(|v| v.to_string())
     }
 
+    // Updates just these keys in the file and leaves the rest as they were,
+    // so a quick toggle does not also save settings still being edited
+    pub fn write_keys_to_file(&self, path: &str, keys: &[&str]) -> Result<(), String> {
+        let file_content = match fs::read_to_string(path) {
+            Ok(value) => value,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Unhandled io error: {}", e)),
+        };
+        let mut kv = KeysAndValues::new_from_str(&file_content).map_err(|e| e.to_string())?;
+        let current = self.to_kv()?;
+
+        for key in keys {
+            match current.entries().find(|(k, _)| k == key) {
+                Some((_, value)) => kv.set(key, &value, |v| v.to_string()),
+                None => kv.remove(key),
+            }
+        }
+        kv.write_to_file(path).map_err(|v| v.to_string())
+    }
+
     fn to_kv(&self) -> Result<KeysAndValues, String> {
         let mut kv = KeysAndValues::new();
 
         kv.set("timers", &self.timers, |v| timers_to_string(v));
         kv.set("tick_interval", &self.tick_interval, |v| v.to_string());
         kv.set("play_once", &self.play_once, |v| v.to_string());
+        kv.set("keep_adjustments", &self.keep_adjustments, |v| {
+            v.to_string()
+        });
         kv.set("volume", &self.volume, |v| v.to_string());
         kv.set("use_custom_sound", &self.use_custom_sound, |v| {
             v.to_string()
@@ -97,29 +718,132 @@ impl AppSettings {
         kv.set("custom_sound_location", &self.custom_sound_location, |v| {
             v.to_string()
         });
+        kv.set("catch_up", &self.catch_up, |v| v.to_string());
+        kv.set("debug_overlay", &self.debug_overlay, |v| v.to_string());
+        kv.set("profile", &self.profile, |v| v.to_string());
+        kv.set("sequence", &self.sequence, |v| v.to_string());
+        kv.set("keybindings", &self.keybindings, keybindings_to_string);
+        if let Some(mini_window) = &self.mini_window {
+            kv.set("mini_window", mini_window, |v| v.to_string());
+        }
+        kv.set("mini_click_through", &self.mini_click_through, |v| {
+            v.to_string()
+        });
+        kv.set("progress_style", &self.progress_style, |v| v.to_string());
+        kv.set("ring_thickness", &self.ring_thickness, |v| v.to_string());
+        kv.set("session_panel", &self.session_panel, |v| v.to_string());
+        kv.set("effect_flash", &self.effects.flash, |v| v.to_string());
+        kv.set("effect_pulse_seconds", &self.effects.pulse_seconds, |v| {
+            v.to_string()
+        });
+        kv.set("effect_animate", &self.effects.animate, |v| v.to_string());
+        kv.set("reduce_motion", &self.effects.reduce_motion, |v| {
+            v.to_string()
+        });
+        kv.set("effect_intensity", &self.effects.intensity, |v| {
+            v.to_string()
+        });
+        kv.set("effect_duration", &self.effects.duration_ms, |v| {
+            v.to_string()
+        });
+
+        kv.set("theme", &self.theme, |v| v.to_string());
+        kv.set("font_size", &self.font_size, |v| v.to_string());
+        kv.set("timer_font_scale", &self.timer_font_scale, |v| {
+            v.to_string()
+        });
+        kv.set("palettes", &self.palettes, |v| palettes_to_string(v));
+        kv.set("title_format", &self.title_format, |v| v.to_string());
+        kv.set("notifications", &self.notifications, |v| v.to_string());
+        kv.set("notify_before_seconds", &self.notify_before_seconds, |v| {
+            v.to_string()
+        });
+        kv.set("notifications_bus", &self.notifications_bus, |v| {
+            v.to_string()
+        });
+        kv.set("mpris", &self.mpris, |v| v.to_string());
+        kv.set("mpris_bus", &self.mpris_bus, |v| v.to_string());
+        kv.set("osc", &self.osc, |v| v.to_string());
+        kv.set("osc_target", &self.osc_target, |v| v.to_string());
+        kv.set("osc_bind", &self.osc_bind, |v| v.to_string());
+        kv.set("osc_listen_port", &self.osc_listen_port, |v| v.to_string());
+        kv.set("osc_remaining_ms", &self.osc_remaining_ms, |v| {
+            v.to_string()
+        });
+        kv.set("mqtt", &self.mqtt.enabled, |v| v.to_string());
+        kv.set("mqtt_broker", &self.mqtt.broker, |v| v.to_string());
+        kv.set("mqtt_topic", &self.mqtt.topic, |v| v.to_string());
+        kv.set("mqtt_username", &self.mqtt.username, |v| v.to_string());
+        kv.set("mqtt_password", &self.mqtt.password, |v| v.to_string());
+        kv.set("mqtt_discovery", &self.mqtt.discovery, |v| v.to_string());
+        kv.set("script", &self.script, |v| v.to_string());
+        kv.set("shuffle", &self.shuffle.shuffle, |v| v.to_string());
+        kv.set("shuffle_pick", &self.shuffle.pick, |v| v.to_string());
+        kv.set("shuffle_min_secs", &self.shuffle.min_secs, |v| {
+            v.to_string()
+        });
+        kv.set("shuffle_max_secs", &self.shuffle.max_secs, |v| {
+            v.to_string()
+        });
+        kv.set("shuffle_seed", &self.shuffle.seed, |v| {
+            v.map(|seed| seed.to_string()).unwrap_or_default()
+        });
 
         Ok(kv)
     }
 }
 
-fn parse_timers(line: &str) -> Result<Vec<(u64, (u8, u8, u8))>, String> {
+pub fn parse_timers(line: &str) -> Result<Vec<Timer>, String> {
     line.split(",")
+        .filter(|entry| !entry.is_empty())
         .map(|entry| {
-            let (millis, color) = entry
-                .split_once("#")
+            let mut parts = entry.splitn(3, "#");
+            let time = parts.next().unwrap_or_default();
+            let color = parts
+                .next()
                 .ok_or_else(|| format!("Missing '#' in timer color '{}'", entry))?;
+            let label = parts.next().unwrap_or_default();
+
+            let color = parse_color(color)?;
 
-            let millis: u64 = millis
+            // Anything but automatic advance is written before the time, e.g. hold:5000
+            let (advance, time) = match time.split_once(':') {
+                Some((advance, time)) => (advance.parse::<Advance>()?, time),
+                None => (Advance::Auto, time),
+            };
+
+            // Count-up timers carry their kind in place of a duration
+            if let Ok(kind) = time.parse::<TimerKind>() {
+                return Ok(Timer {
+                    millis: 0,
+                    color,
+                    kind,
+                    label: sanitize_label(label),
+                    advance,
+                });
+            }
+
+            let millis: u64 = time
                 .parse()
-                .map_err(|e| format!("Invalid time provided in timer '{}': {}", millis, e))?;
+                .map_err(|e| format!("Invalid time provided in timer '{}': {}", time, e))?;
 
-            let color = parse_color(color)?;
-            Ok((millis, color))
+            Ok(Timer {
+                advance,
+                ..Timer::new(millis, color).with_label(label)
+            })
         })
         .collect()
 }
 
-fn parse_color(hex: &str) -> Result<(u8, u8, u8), String> {
+// An empty seed means none
+fn parse_seed(value: &str) -> Result<Option<u64>, std::num::ParseIntError> {
+    match value.is_empty() {
+        true => Ok(None),
+        false => value.parse::<u64>().map(Some),
+    }
+}
+
+pub fn parse_color(hex: &str) -> Result<(u8, u8, u8), String> {
     if hex.len() != 6 {
         return Err(format!("Colors should be 6 hex characters"));
     }
@@ -134,10 +858,57 @@ fn parse_color(hex: &str) -> Result<(u8, u8, u8), String> {
     Ok((r, g, b))
 }
 
-fn timers_to_string(value: &Vec<(u64, (u8, u8, u8))>) -> String {
+pub fn timers_to_string(value: &[Timer]) -> String {
     value
         .iter()
-        .map(|v| format!("{}#{:02x}{:02x}{:02x}", v.0, v.1.0, v.1.1, v.1.2))
+        .map(|v| {
+            let mut time = match v.kind {
+                TimerKind::Countdown => v.millis.to_string(),
+                kind => kind.to_string(),
+            };
+            if v.advance != Advance::Auto {
+                time = format!("{}:{}", v.advance, time);
+            }
+            let (r, g, b) = v.color;
+            match v.label.is_empty() {
+                true => format!("{}#{:02x}{:02x}{:02x}", time, r, g, b),
+                false => format!("{}#{:02x}{:02x}{:02x}#{}", time, r, g, b, v.label),
+            }
+        })
         .collect::<Vec<_>>()
         .join(",")
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+
+    use super::*;
+
+    #[test]
+    fn writes_only_the_given_keys() {
+        let path = env::temp_dir().join(format!("interval-timer-config-{}.txt", process::id()));
+        let path = path.to_string_lossy().to_string();
+        fs::write(&path, "play_once=true\nvolume=0.25\nunknown=kept").unwrap();
+
+        let mut settings = AppSettings::new_from_str(String::new()).unwrap();
+        settings.play_once = false;
+        settings.volume = 0.75;
+        settings.mini_click_through = true;
+        settings.mini_window = None;
+        settings
+            .write_keys_to_file(&path, &["volume", "mini_click_through", "mini_window"])
+            .unwrap();
+
+        let saved = AppSettings::new_from_file(&path).unwrap();
+        let raw = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(saved.play_once);
+        assert_eq!(saved.volume, 0.75);
+        assert!(saved.mini_click_through);
+        assert!(raw.contains("unknown=kept"));
+        assert!(!raw.contains("mini_window"));
+    }
+}
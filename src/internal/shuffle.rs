@@ -0,0 +1,89 @@
+use crate::internal::random::Rng;
+use crate::internal::settings_parser::ShuffleSettings;
+use crate::internal::timer::{Timer, TimerKind};
+
+// The intervals for one round, drawn from the configured timers
+pub fn deal(pool: &[Timer], settings: &ShuffleSettings, rng: &mut Rng) -> Vec<Timer> {
+    let mut round = pool.to_vec();
+
+    if settings.pick > 0 && settings.pick < round.len() {
+        // Keeps the picked intervals in their configured order
+        let mut picked: Vec<usize> = (0..round.len()).collect();
+        rng.shuffle(&mut picked);
+        picked.truncate(settings.pick);
+        picked.sort_unstable();
+        round = picked.into_iter().map(|i| pool[i].clone()).collect();
+    }
+
+    if settings.shuffle {
+        rng.shuffle(&mut round);
+    }
+
+    if settings.max_secs > 0 {
+        let min = settings.min_secs.clamp(1, settings.max_secs);
+        for timer in round.iter_mut() {
+            if timer.kind == TimerKind::Countdown {
+                timer.millis = rng.range(min, settings.max_secs) * 1000;
+            }
+        }
+    }
+
+    round
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> Vec<Timer> {
+        (1..=8)
+            .map(|secs| Timer::new(secs * 1000, (0, 0, 0)))
+            .collect()
+    }
+
+    fn settings(seed: u64) -> ShuffleSettings {
+        ShuffleSettings {
+            shuffle: true,
+            pick: 5,
+            min_secs: 5,
+            max_secs: 60,
+            seed: Some(seed),
+        }
+    }
+
+    fn rounds(seed: u64) -> Vec<Vec<Timer>> {
+        let mut rng = Rng::new(seed);
+        (0..4)
+            .map(|_| deal(&pool(), &settings(seed), &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn the_same_seed_deals_the_same_rounds() {
+        assert_eq!(rounds(42), rounds(42));
+        assert_ne!(rounds(42), rounds(43));
+    }
+
+    #[test]
+    fn deals_within_the_settings() {
+        for round in rounds(7) {
+            assert_eq!(round.len(), 5);
+            assert!(
+                round
+                    .iter()
+                    .all(|timer| (5_000..=60_000).contains(&timer.millis))
+            );
+        }
+    }
+
+    #[test]
+    fn picks_keep_their_configured_order() {
+        let settings = ShuffleSettings {
+            pick: 3,
+            ..ShuffleSettings::default()
+        };
+        let round = deal(&pool(), &settings, &mut Rng::new(1));
+        assert_eq!(round.len(), 3);
+        assert!(round.windows(2).all(|pair| pair[0].millis < pair[1].millis));
+    }
+}
@@ -7,6 +7,12 @@ pub struct SoundFile {
     ready: bool,
 }
 
+impl Default for SoundFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SoundFile {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+use crate::internal::history::Record;
+
+const DAYS_SHOWN: u64 = 14;
+const WEEKS_SHOWN: u64 = 8;
+const MONTHS_SHOWN: u32 = 6;
+
+pub struct Statistics {
+    pub days: Vec<(NaiveDate, Duration)>,
+    pub weeks: Vec<(NaiveDate, Duration)>,
+    pub months: Vec<(NaiveDate, Duration)>,
+    pub colors: Vec<((u8, u8, u8), Duration)>,
+    pub labels: Vec<(String, Duration)>,
+    pub total: Duration,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub completion_rate: Option<f32>,
+}
+
+impl Statistics {
+    pub fn new(records: &[Record], today: NaiveDate) -> Self {
+        let dated: Vec<(NaiveDate, &Record)> = records
+            .iter()
+            .filter_map(|record| Some((local_date(record.finished_at)?, record)))
+            .collect();
+
+        let days = (0..DAYS_SHOWN)
+            .rev()
+            .filter_map(|i| today.checked_sub_days(Days::new(i)))
+            .map(|day| (day, time_between(&dated, day, day)))
+            .collect();
+
+        let this_week = week_start(today);
+        let weeks = (0..WEEKS_SHOWN)
+            .rev()
+            .filter_map(|i| this_week.checked_sub_days(Days::new(i * 7)))
+            .map(|week| {
+                let end = week + Days::new(6);
+                (week, time_between(&dated, week, end))
+            })
+            .collect();
+
+        let this_month = today.with_day(1).unwrap_or(today);
+        let months = (0..MONTHS_SHOWN)
+            .rev()
+            .filter_map(|i| this_month.checked_sub_months(Months::new(i)))
+            .map(|month| {
+                let end = month + Months::new(1) - Days::new(1);
+                (month, time_between(&dated, month, end))
+            })
+            .collect();
+
+        let mut per_color: HashMap<(u8, u8, u8), Duration> = HashMap::new();
+        for record in records {
+            *per_color.entry(record.color).or_default() += Duration::from_millis(record.elapsed_ms);
+        }
+        let mut colors: Vec<_> = per_color.into_iter().collect();
+        colors.sort_by_key(|color| Reverse(color.1));
+
+        let mut per_label: HashMap<&str, Duration> = HashMap::new();
+        for record in records.iter().filter(|record| !record.label.is_empty()) {
+            *per_label.entry(&record.label).or_default() +=
+                Duration::from_millis(record.elapsed_ms);
+        }
+        let mut labels: Vec<_> = per_label
+            .into_iter()
+            .map(|(label, time)| (label.to_string(), time))
+            .collect();
+        labels.sort_by_key(|label| Reverse(label.1));
+
+        let total = records
+            .iter()
+            .map(|record| Duration::from_millis(record.elapsed_ms))
+            .sum();
+
+        let active_days: BTreeSet<NaiveDate> = dated
+            .iter()
+            .filter(|(_, record)| record.completed)
+            .map(|(day, _)| *day)
+            .collect();
+        let (current_streak, longest_streak) = streaks(&active_days, today);
+
+        let completion_rate = match records.len() {
+            0 => None,
+            len => {
+                let completed = records.iter().filter(|record| record.completed).count();
+                Some(completed as f32 / len as f32)
+            }
+        };
+
+        Self {
+            days,
+            weeks,
+            months,
+            colors,
+            labels,
+            total,
+            current_streak,
+            longest_streak,
+            completion_rate,
+        }
+    }
+}
+
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    let utc = DateTime::from_timestamp(timestamp, 0)?;
+    Some(utc.with_timezone(&Local).date_naive())
+}
+
+fn week_start(day: NaiveDate) -> NaiveDate {
+    let offset = day.weekday().num_days_from_monday() as u64;
+    day.checked_sub_days(Days::new(offset)).unwrap_or(day)
+}
+
+fn time_between(dated: &[(NaiveDate, &Record)], first: NaiveDate, last: NaiveDate) -> Duration {
+    dated
+        .iter()
+        .filter(|(day, _)| *day >= first && *day <= last)
+        .map(|(_, record)| Duration::from_millis(record.elapsed_ms))
+        .sum()
+}
+
+fn streaks(active_days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for day in active_days {
+        run = match previous {
+            Some(prev) if prev.succ_opt() == Some(*day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    // A streak is still alive until a full day passes without activity
+    let yesterday = today.pred_opt().unwrap_or(today);
+    let current = match previous {
+        Some(last) if last == today || last == yesterday => run,
+        _ => 0,
+    };
+
+    (current, longest)
+}
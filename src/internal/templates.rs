@@ -0,0 +1,374 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+use crate::internal::keys_and_values::KeysAndValues;
+use crate::internal::timer::{Timer, sanitize_label};
+
+const WORK_COLOR: (u8, u8, u8) = (0xe6, 0x4a, 0x19);
+const REST_COLOR: (u8, u8, u8) = (0x43, 0xa0, 0x47);
+const LONG_BREAK_COLOR: (u8, u8, u8) = (0x1e, 0x88, 0xe5);
+
+// Also the limits of the editor, a hand-edited file is held to them too
+pub const MAX_ROUNDS: u32 = 100;
+pub const MAX_LONG_BREAK_EVERY: u32 = 20;
+pub const MAX_EXERCISES: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemplateKind {
+    Tabata,
+    Emom,
+    Amrap,
+    Pomodoro,
+    Ladder,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    pub kind: TemplateKind,
+    pub rounds: u32,
+    pub work_ms: u64,
+    pub rest_ms: u64,
+    pub step_ms: u64,
+    pub long_break_ms: u64,
+    pub long_break_every: u32,
+    pub exercises: Vec<String>,
+}
+
+pub struct TemplateLibrary {
+    path: String,
+    user: Vec<Template>,
+}
+
+impl TemplateKind {
+    pub const ALL: [TemplateKind; 5] = [
+        TemplateKind::Tabata,
+        TemplateKind::Emom,
+        TemplateKind::Amrap,
+        TemplateKind::Pomodoro,
+        TemplateKind::Ladder,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TemplateKind::Tabata => "Tabata",
+            TemplateKind::Emom => "EMOM",
+            TemplateKind::Amrap => "AMRAP",
+            TemplateKind::Pomodoro => "Pomodoro",
+            TemplateKind::Ladder => "Ladder",
+        }
+    }
+
+    pub fn uses_rest(&self) -> bool {
+        *self != TemplateKind::Emom
+    }
+
+    pub fn uses_step(&self) -> bool {
+        *self == TemplateKind::Ladder
+    }
+
+    pub fn uses_long_break(&self) -> bool {
+        *self == TemplateKind::Pomodoro
+    }
+
+    pub fn uses_exercises(&self) -> bool {
+        *self != TemplateKind::Pomodoro
+    }
+}
+
+impl FromStr for TemplateKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "tabata" => Ok(TemplateKind::Tabata),
+            "emom" => Ok(TemplateKind::Emom),
+            "amrap" => Ok(TemplateKind::Amrap),
+            "pomodoro" => Ok(TemplateKind::Pomodoro),
+            "ladder" => Ok(TemplateKind::Ladder),
+            _ => Err(format!("Unknown template type '{}'", value)),
+        }
+    }
+}
+
+impl fmt::Display for TemplateKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateKind::Tabata => write!(f, "tabata"),
+            TemplateKind::Emom => write!(f, "emom"),
+            TemplateKind::Amrap => write!(f, "amrap"),
+            TemplateKind::Pomodoro => write!(f, "pomodoro"),
+            TemplateKind::Ladder => write!(f, "ladder"),
+        }
+    }
+}
+
+impl Template {
+    pub fn new(kind: TemplateKind) -> Self {
+        let base = Self {
+            name: kind.label().to_string(),
+            kind,
+            rounds: 8,
+            work_ms: 20_000,
+            rest_ms: 10_000,
+            step_ms: 0,
+            long_break_ms: 0,
+            long_break_every: 0,
+            exercises: vec![],
+        };
+
+        match kind {
+            TemplateKind::Tabata => base,
+            TemplateKind::Emom => Self {
+                rounds: 10,
+                work_ms: 60_000,
+                rest_ms: 0,
+                ..base
+            },
+            TemplateKind::Amrap => Self {
+                rounds: 1,
+                work_ms: 20 * 60_000,
+                rest_ms: 0,
+                ..base
+            },
+            TemplateKind::Pomodoro => Self {
+                rounds: 4,
+                work_ms: 25 * 60_000,
+                rest_ms: 5 * 60_000,
+                long_break_ms: 15 * 60_000,
+                long_break_every: 4,
+                ..base
+            },
+            TemplateKind::Ladder => Self {
+                rounds: 5,
+                work_ms: 10_000,
+                rest_ms: 10_000,
+                step_ms: 10_000,
+                ..base
+            },
+        }
+    }
+
+    pub fn built_in() -> Vec<Template> {
+        TemplateKind::ALL.into_iter().map(Template::new).collect()
+    }
+
+    pub fn expand(&self) -> Vec<Timer> {
+        let mut timers = vec![];
+
+        for round in 0..self.rounds {
+            let is_last = round + 1 == self.rounds;
+
+            match self.kind {
+                TemplateKind::Tabata => {
+                    timers.push(self.work(self.work_ms, round, "Work"));
+                    if self.rest_ms > 0 {
+                        timers.push(Timer::new(self.rest_ms, REST_COLOR).with_label("Rest"));
+                    }
+                }
+                TemplateKind::Emom => {
+                    let fallback = format!("Minute {}", round + 1);
+                    timers.push(self.work(self.work_ms, round, &fallback));
+                }
+                TemplateKind::Amrap => {
+                    let label = match self.exercises.is_empty() {
+                        true => "AMRAP".to_string(),
+                        false => self.exercises.join(" + "),
+                    };
+                    timers.push(Timer::new(self.work_ms, WORK_COLOR).with_label(&label));
+                    if !is_last && self.rest_ms > 0 {
+                        timers.push(Timer::new(self.rest_ms, REST_COLOR).with_label("Rest"));
+                    }
+                }
+                TemplateKind::Pomodoro => {
+                    timers.push(Timer::new(self.work_ms, WORK_COLOR).with_label("Focus"));
+
+                    let long_break = self.long_break_every > 0
+                        && (round + 1) % self.long_break_every == 0
+                        && self.long_break_ms > 0;
+                    if long_break {
+                        let timer = Timer::new(self.long_break_ms, LONG_BREAK_COLOR);
+                        timers.push(timer.with_label("Long break"));
+                    } else if self.rest_ms > 0 {
+                        timers.push(Timer::new(self.rest_ms, REST_COLOR).with_label("Break"));
+                    }
+                }
+                TemplateKind::Ladder => {
+                    let work_ms = self.work_ms + self.step_ms * round as u64;
+                    let fallback = format!("Step {}", round + 1);
+                    timers.push(self.work(work_ms, round, &fallback));
+                    if !is_last && self.rest_ms > 0 {
+                        timers.push(Timer::new(self.rest_ms, REST_COLOR).with_label("Rest"));
+                    }
+                }
+            }
+        }
+
+        timers
+    }
+
+    fn work(&self, millis: u64, round: u32, fallback: &str) -> Timer {
+        let label = match self.exercises.is_empty() {
+            true => fallback,
+            false => &self.exercises[round as usize % self.exercises.len()],
+        };
+
+        Timer::new(millis, WORK_COLOR).with_label(label)
+    }
+}
+
+impl TemplateLibrary {
+    pub fn new_from_file(path: &str) -> Result<Self, String> {
+        let file_content = match fs::read_to_string(path) {
+            Ok(value) => value,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Unhandled io error: {}", e)),
+        };
+
+        let kv = KeysAndValues::new_from_str(&file_content).map_err(|e| e.to_string())?;
+        let mut user = kv
+            .entries()
+            .map(|(name, value)| parse_template(name, value))
+            .collect::<Result<Vec<_>, _>>()?;
+        user.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self {
+            path: path.to_string(),
+            user,
+        })
+    }
+
+    pub fn all(&self) -> Vec<Template> {
+        let mut all = Template::built_in();
+        all.retain(|template| !self.user.iter().any(|v| v.name == template.name));
+        all.extend(self.user.iter().cloned());
+        all
+    }
+
+    pub fn save(&mut self, template: Template) -> Result<(), String> {
+        if template.name.is_empty() {
+            return Err("Templates need a name".to_string());
+        }
+
+        self.user.retain(|v| v.name != template.name);
+        self.user.push(template);
+        self.user.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut kv = KeysAndValues::new();
+        for template in &self.user {
+            kv.set(&template.name, template, template_to_string);
+        }
+        kv.write_to_file(&self.path).map_err(|v| v.to_string())
+    }
+}
+
+pub fn sanitize_name(name: &str) -> String {
+    sanitize_label(name)
+        .replace(['=', ';', '|', '/', '\\'], "")
+        .trim()
+        .to_string()
+}
+
+fn parse_template(name: &str, value: &str) -> Result<Template, String> {
+    let mut fields = value.split(';');
+    let kind = fields.next().unwrap_or_default().parse::<TemplateKind>()?;
+    let mut template = Template {
+        name: name.to_string(),
+        ..Template::new(kind)
+    };
+
+    for field in fields {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("Missing '=' in template field '{}'", field))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|e| format!("Invalid value '{}' for '{}': {}", value, key, e))
+        };
+
+        match key {
+            "rounds" => template.rounds = number()?.clamp(1, MAX_ROUNDS as u64) as u32,
+            "work" => template.work_ms = number()?,
+            "rest" => template.rest_ms = number()?,
+            "step" => template.step_ms = number()?,
+            "long_break" => template.long_break_ms = number()?,
+            "long_break_every" => {
+                template.long_break_every = number()?.min(MAX_LONG_BREAK_EVERY as u64) as u32
+            }
+            "exercises" => {
+                template.exercises = value
+                    .split('|')
+                    .filter(|v| !v.is_empty())
+                    .take(MAX_EXERCISES)
+                    .map(|v| v.to_string())
+                    .collect()
+            }
+            _ => return Err(format!("Unknown template field '{}'", key)),
+        }
+    }
+
+    Ok(template)
+}
+
+fn template_to_string(template: &Template) -> String {
+    format!(
+        "{};rounds={};work={};rest={};step={};long_break={};long_break_every={};exercises={}",
+        template.kind,
+        template.rounds,
+        template.work_ms,
+        template.rest_ms,
+        template.step_ms,
+        template.long_break_ms,
+        template.long_break_every,
+        template.exercises.join("|")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pomodoro_without_breaks_has_no_empty_intervals() {
+        let template = Template {
+            rest_ms: 0,
+            long_break_ms: 0,
+            ..Template::new(TemplateKind::Pomodoro)
+        };
+        let timers = template.expand();
+        assert_eq!(timers.len(), template.rounds as usize);
+        assert!(timers.iter().all(|timer| timer.millis > 0));
+    }
+
+    #[test]
+    fn holds_hand_edited_templates_to_the_editor_limits() {
+        let exercises = vec!["x"; MAX_EXERCISES + 5].join("|");
+        let value = format!(
+            "pomodoro;rounds=4000000000;long_break_every=99;exercises={}",
+            exercises
+        );
+        let template = parse_template("Long", &value).unwrap();
+        assert_eq!(template.rounds, MAX_ROUNDS);
+        assert_eq!(template.long_break_every, MAX_LONG_BREAK_EVERY);
+        assert_eq!(template.exercises.len(), MAX_EXERCISES);
+
+        assert_eq!(parse_template("None", "tabata;rounds=0").unwrap().rounds, 1);
+        assert!(parse_template("Bad", "tabata;rounds=-1").is_err());
+    }
+
+    #[test]
+    fn templates_need_a_name() {
+        let mut library = TemplateLibrary {
+            path: String::new(),
+            user: vec![],
+        };
+        let template = Template {
+            name: String::new(),
+            ..Template::new(TemplateKind::Tabata)
+        };
+        assert!(library.save(template).is_err());
+    }
+}
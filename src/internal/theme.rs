@@ -0,0 +1,176 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::internal::settings_parser::parse_color;
+use crate::internal::templates::sanitize_name;
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub name: String,
+    pub colors: Vec<(u8, u8, u8)>,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::HighContrast];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::HighContrast => "High contrast",
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "dark" => Ok(Theme::Dark),
+            "light" => Ok(Theme::Light),
+            "high_contrast" => Ok(Theme::HighContrast),
+            _ => Err("Expected one of dark, light or high_contrast".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Theme::Dark => write!(f, "dark"),
+            Theme::Light => write!(f, "light"),
+            Theme::HighContrast => write!(f, "high_contrast"),
+        }
+    }
+}
+
+impl Palette {
+    // Okabe-Ito and Paul Tol's bright scheme stay distinguishable
+    // with the common forms of color blindness
+    pub fn built_in() -> Vec<Palette> {
+        vec![
+            Palette::from_hex(
+                "Okabe-Ito",
+                &[
+                    "e69f00", "56b4e9", "009e73", "f0e442", "0072b2", "d55e00", "cc79a7",
+                ],
+            ),
+            Palette::from_hex(
+                "Tol bright",
+                &[
+                    "4477aa", "ee6677", "228833", "ccbb44", "66ccee", "aa3377", "bbbbbb",
+                ],
+            ),
+            Palette::from_hex(
+                "Classic",
+                &["ff0000", "00ff00", "0000ff", "ffff00", "ff00ff", "00ffff"],
+            ),
+        ]
+    }
+
+    fn from_hex(name: &str, colors: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            colors: colors.iter().filter_map(|v| parse_color(v).ok()).collect(),
+        }
+    }
+
+    // Gives every distinct color in the list the next palette color,
+    // so intervals that shared a color still share one afterwards
+    pub fn recolor(&self, colors: &[(u8, u8, u8)]) -> Vec<(u8, u8, u8)> {
+        if self.colors.is_empty() {
+            return colors.to_vec();
+        }
+
+        let mut distinct: Vec<(u8, u8, u8)> = vec![];
+        colors
+            .iter()
+            .map(|color| {
+                let index = match distinct.iter().position(|v| v == color) {
+                    Some(index) => index,
+                    None => {
+                        distinct.push(*color);
+                        distinct.len() - 1
+                    }
+                };
+                self.colors[index % self.colors.len()]
+            })
+            .collect()
+    }
+}
+
+// WCAG relative luminance of an sRGB color
+pub fn luminance(color: (u8, u8, u8)) -> f32 {
+    let channel = |v: u8| {
+        let v = v as f32 / 255.0;
+        match v <= 0.03928 {
+            true => v / 12.92,
+            false => ((v + 0.055) / 1.055).powf(2.4),
+        }
+    };
+
+    0.2126 * channel(color.0) + 0.7152 * channel(color.1) + 0.0722 * channel(color.2)
+}
+
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+// Black or white, whichever reads better on the given background
+pub fn contrast_text_color(background: (u8, u8, u8)) -> (u8, u8, u8) {
+    let black = (0, 0, 0);
+    let white = (255, 255, 255);
+
+    match contrast_ratio(background, black) > contrast_ratio(background, white) {
+        true => black,
+        false => white,
+    }
+}
+
+// e.g. "Gym:ff0000|00ff00;Calm:88aacc|ccddee"
+pub fn parse_palettes(line: &str) -> Result<Vec<Palette>, String> {
+    line.split(';')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, colors) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("Missing ':' in palette '{}'", entry))?;
+            let colors = colors
+                .split('|')
+                .filter(|v| !v.is_empty())
+                .map(|v| parse_color(v.trim_start_matches('#')))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Palette {
+                name: sanitize_name(name),
+                colors,
+            })
+        })
+        .collect()
+}
+
+pub fn palettes_to_string(palettes: &[Palette]) -> String {
+    palettes
+        .iter()
+        .map(|palette| {
+            let colors = palette
+                .colors
+                .iter()
+                .map(|(r, g, b)| format!("{:02x}{:02x}{:02x}", r, g, b))
+                .collect::<Vec<_>>()
+                .join("|");
+            format!("{}:{}", palette.name, colors)
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerKind {
+    #[default]
+    Countdown,
+    OpenEnded,
+    Stopwatch,
+}
+
+// When the timer moves on to the next interval
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Advance {
+    #[default]
+    Auto,
+    // Waits for Start once this interval has finished
+    Confirm,
+    // Waits for Start before this interval begins
+    Hold,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timer {
+    pub millis: u64,
+    pub color: (u8, u8, u8),
+    pub kind: TimerKind,
+    pub label: String,
+    pub advance: Advance,
+}
+
+impl Timer {
+    pub fn new(millis: u64, color: (u8, u8, u8)) -> Self {
+        Self {
+            millis,
+            color,
+            kind: TimerKind::Countdown,
+            label: String::new(),
+            advance: Advance::Auto,
+        }
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = sanitize_label(label);
+        self
+    }
+
+    pub fn counts_up(&self) -> bool {
+        self.kind != TimerKind::Countdown
+    }
+}
+
+impl TimerKind {
+    pub const ALL: [TimerKind; 3] = [
+        TimerKind::Countdown,
+        TimerKind::OpenEnded,
+        TimerKind::Stopwatch,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimerKind::Countdown => "Countdown",
+            TimerKind::OpenEnded => "Until next",
+            TimerKind::Stopwatch => "Stopwatch",
+        }
+    }
+}
+
+impl FromStr for TimerKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "open" => Ok(TimerKind::OpenEnded),
+            "stopwatch" => Ok(TimerKind::Stopwatch),
+            _ => Err(format!("Unknown timer kind '{}'", value)),
+        }
+    }
+}
+
+impl fmt::Display for TimerKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimerKind::Countdown => write!(f, "countdown"),
+            TimerKind::OpenEnded => write!(f, "open"),
+            TimerKind::Stopwatch => write!(f, "stopwatch"),
+        }
+    }
+}
+
+impl Advance {
+    pub const ALL: [Advance; 3] = [Advance::Auto, Advance::Confirm, Advance::Hold];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Advance::Auto => "Moves on",
+            Advance::Confirm => "Wait after",
+            Advance::Hold => "Wait before",
+        }
+    }
+}
+
+impl FromStr for Advance {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Advance::Auto),
+            "confirm" => Ok(Advance::Confirm),
+            "hold" => Ok(Advance::Hold),
+            _ => Err(format!("Unknown advance mode '{}'", value)),
+        }
+    }
+}
+
+impl fmt::Display for Advance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Advance::Auto => write!(f, "auto"),
+            Advance::Confirm => write!(f, "confirm"),
+            Advance::Hold => write!(f, "hold"),
+        }
+    }
+}
+
+// Labels are stored inline in the comma separated timers list
+pub fn sanitize_label(label: &str) -> String {
+    label
+        .chars()
+        .filter(|c| !matches!(c, ',' | '#' | '\n' | '\r'))
+        .collect()
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+pub const DEFAULT_TITLE_FORMAT: &str = "{time} - {label} ({index}/{count}) - Interval Timer";
+
+pub struct TitleFields {
+    pub remaining: Duration,
+    pub elapsed: Duration,
+    pub counts_up: bool,
+    pub label: String,
+    pub index: usize,
+    pub count: usize,
+    pub round: u32,
+    pub fraction: f32,
+    pub running: bool,
+}
+
+// Fills in {time}, {remaining}, {elapsed}, {label}, {index}, {count},
+// {round}, {percent} and {state}, leaving anything else as written
+pub fn format_title(format: &str, fields: &TitleFields) -> String {
+    let time = match fields.counts_up {
+        true => format!("+{}", format_seconds(fields.elapsed)),
+        false => format_seconds(fields.remaining),
+    };
+    let label = match fields.label.is_empty() {
+        true => format!("Interval {}", fields.index + 1),
+        false => fields.label.clone(),
+    };
+    let state = match fields.running {
+        true => "Running",
+        false => "Paused",
+    };
+
+    format
+        .replace("{time}", &time)
+        .replace("{remaining}", &format_seconds(fields.remaining))
+        .replace("{elapsed}", &format_seconds(fields.elapsed))
+        .replace("{label}", &label)
+        .replace("{index}", &(fields.index + 1).to_string())
+        .replace("{count}", &fields.count.to_string())
+        .replace("{round}", &(fields.round + 1).to_string())
+        .replace(
+            "{percent}",
+            &((fields.fraction * 100.0).round() as u32).to_string(),
+        )
+        .replace("{state}", state)
+}
+
+// Whole seconds only, so the title changes once a second at most
+fn format_seconds(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
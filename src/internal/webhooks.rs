@@ -0,0 +1,612 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+
+use crate::internal::json::Json;
+use crate::internal::keys_and_values::KeysAndValues;
+use crate::internal::templates::sanitize_name;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookEvent {
+    SessionStart,
+    IntervalFinish,
+    SessionComplete,
+    SessionAbandoned,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Webhook {
+    pub name: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    // Empty sends every field as a JSON object
+    pub template: String,
+}
+
+// What a payload can refer to, the interval is the one that
+// finished or, for session events, the current one
+#[derive(Clone, Debug, Default)]
+pub struct WebhookFields {
+    pub profile: String,
+    pub label: String,
+    pub index: usize,
+    pub count: usize,
+    pub round: u32,
+    pub duration_ms: u64,
+    pub elapsed_ms: u64,
+    pub session_elapsed_ms: u64,
+    // The interval ran out while the app was hidden or asleep
+    pub missed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeliveryStatus {
+    Pending,
+    Retrying(String),
+    Delivered(u16),
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Delivery {
+    pub id: u64,
+    pub webhook: String,
+    pub event: WebhookEvent,
+    pub sent_at: i64,
+    pub attempts: u32,
+    pub status: DeliveryStatus,
+}
+
+// Saved webhooks, deliveries run on a background thread per URL,
+// so a slow endpoint only holds up itself, and report back through the shared log
+pub struct Webhooks {
+    path: String,
+    hooks: Vec<Webhook>,
+    workers: HashMap<String, Sender<Job>>,
+    log: Arc<Mutex<VecDeque<Delivery>>>,
+    next_id: u64,
+}
+
+struct Job {
+    id: u64,
+    url: String,
+    body: String,
+    attempts: u32,
+    due: Instant,
+}
+
+const MAX_ATTEMPTS: u32 = 5;
+const FIRST_RETRY: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const LOG_SIZE: usize = 50;
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 4] = [
+        WebhookEvent::SessionStart,
+        WebhookEvent::IntervalFinish,
+        WebhookEvent::SessionComplete,
+        WebhookEvent::SessionAbandoned,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WebhookEvent::SessionStart => "Session start",
+            WebhookEvent::IntervalFinish => "Interval finish",
+            WebhookEvent::SessionComplete => "Session complete",
+            WebhookEvent::SessionAbandoned => "Session abandoned",
+        }
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        WebhookEvent::ALL
+            .into_iter()
+            .find(|event| event.to_string() == value)
+            .ok_or_else(|| format!("Unknown webhook event '{}'", value))
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WebhookEvent::SessionStart => write!(f, "session_start"),
+            WebhookEvent::IntervalFinish => write!(f, "interval_finish"),
+            WebhookEvent::SessionComplete => write!(f, "session_complete"),
+            WebhookEvent::SessionAbandoned => write!(f, "session_abandoned"),
+        }
+    }
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            url: String::new(),
+            events: WebhookEvent::ALL.to_vec(),
+            template: String::new(),
+        }
+    }
+}
+
+impl Webhooks {
+    pub fn new_from_file(path: &str) -> Result<Self, String> {
+        let file_content = match fs::read_to_string(path) {
+            Ok(value) => value,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Unhandled io error: {}", e)),
+        };
+
+        let kv = KeysAndValues::new_from_str(&file_content).map_err(|e| e.to_string())?;
+        let mut hooks = kv
+            .entries()
+            .map(|(name, value)| parse_webhook(name, value))
+            .collect::<Result<Vec<_>, _>>()?;
+        hooks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self {
+            path: path.to_string(),
+            hooks,
+            workers: HashMap::new(),
+            log: Arc::new(Mutex::new(VecDeque::new())),
+            next_id: 0,
+        })
+    }
+
+    pub fn all(&self) -> &[Webhook] {
+        &self.hooks
+    }
+
+    pub fn save(&mut self, webhook: Webhook) -> Result<(), String> {
+        parse_url(&webhook.url)?;
+        self.hooks.retain(|v| v.name != webhook.name);
+        self.hooks.push(webhook);
+        self.hooks.sort_by(|a, b| a.name.cmp(&b.name));
+        self.write()
+    }
+
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        self.hooks.retain(|v| v.name != name);
+        self.write()
+    }
+
+    // Queues a POST to every webhook that wants this event, never blocks
+    pub fn fire(&mut self, event: WebhookEvent, fields: &WebhookFields) {
+        let hooks: Vec<Webhook> = self
+            .hooks
+            .iter()
+            .filter(|hook| hook.events.contains(&event))
+            .cloned()
+            .collect();
+        for hook in hooks {
+            self.queue(&hook, event, fields);
+        }
+    }
+
+    pub fn test(&mut self, name: &str) {
+        let fields = WebhookFields {
+            label: "Test".to_string(),
+            count: 1,
+            ..WebhookFields::default()
+        };
+        if let Some(hook) = self.hooks.iter().find(|v| v.name == name).cloned() {
+            self.queue(&hook, WebhookEvent::IntervalFinish, &fields);
+        }
+    }
+
+    // Newest first
+    pub fn log(&self) -> Vec<Delivery> {
+        self.log
+            .lock()
+            .map(|log| log.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // Gives pending deliveries a moment to go out, e.g. before quitting
+    pub fn flush(&self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            let pending = self.log.lock().is_ok_and(|log| {
+                log.iter()
+                    .any(|delivery| delivery.status == DeliveryStatus::Pending)
+            });
+            if !pending {
+                return;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    fn queue(&mut self, hook: &Webhook, event: WebhookEvent, fields: &WebhookFields) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        if let Ok(mut log) = self.log.lock() {
+            if log.len() == LOG_SIZE {
+                log.pop_front();
+            }
+            log.push_back(Delivery {
+                id,
+                webhook: hook.name.clone(),
+                event,
+                sent_at: Utc::now().timestamp(),
+                attempts: 0,
+                status: DeliveryStatus::Pending,
+            });
+        }
+
+        let job = Job {
+            id,
+            url: hook.url.clone(),
+            body: render(&hook.template, event, fields),
+            attempts: 0,
+            due: Instant::now(),
+        };
+        let _ = self.worker(&hook.url).send(job);
+    }
+
+    fn worker(&mut self, url: &str) -> &Sender<Job> {
+        self.workers.entry(url.to_string()).or_insert_with(|| {
+            let (jobs, receiver) = mpsc::channel();
+            let log = self.log.clone();
+            thread::spawn(move || deliver(receiver, log));
+            jobs
+        })
+    }
+
+    fn write(&self) -> Result<(), String> {
+        let mut kv = KeysAndValues::new();
+        for hook in &self.hooks {
+            kv.set(&hook.name, hook, webhook_to_string);
+        }
+        kv.write_to_file(&self.path).map_err(|v| v.to_string())
+    }
+}
+
+// Fills in {event}, {profile}, {label}, {index}, {count}, {round},
+// {duration_ms}, {elapsed_ms}, {session_elapsed_ms}, {missed} and {timestamp}.
+// Text is escaped for use inside a JSON string
+pub fn render(template: &str, event: WebhookEvent, fields: &WebhookFields) -> String {
+    let timestamp = Utc::now().timestamp() as u64;
+    if template.trim().is_empty() {
+        return Json::object(vec![
+            ("event", event.to_string().into()),
+            ("profile", fields.profile.as_str().into()),
+            ("label", fields.label.as_str().into()),
+            ("index", fields.index.into()),
+            ("count", fields.count.into()),
+            ("round", fields.round.into()),
+            ("duration_ms", fields.duration_ms.into()),
+            ("elapsed_ms", fields.elapsed_ms.into()),
+            ("session_elapsed_ms", fields.session_elapsed_ms.into()),
+            ("missed", fields.missed.into()),
+            ("timestamp", timestamp.into()),
+        ])
+        .to_string();
+    }
+
+    let escape = |value: &str| {
+        let quoted = Json::from(value).to_string();
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    template
+        .replace("{event}", &event.to_string())
+        .replace("{profile}", &escape(&fields.profile))
+        .replace("{label}", &escape(&fields.label))
+        .replace("{index}", &fields.index.to_string())
+        .replace("{count}", &fields.count.to_string())
+        .replace("{round}", &fields.round.to_string())
+        .replace("{duration_ms}", &fields.duration_ms.to_string())
+        .replace("{elapsed_ms}", &fields.elapsed_ms.to_string())
+        .replace(
+            "{session_elapsed_ms}",
+            &fields.session_elapsed_ms.to_string(),
+        )
+        .replace("{missed}", &fields.missed.to_string())
+        .replace("{timestamp}", &timestamp.to_string())
+}
+
+// Runs jobs when they are due, a failed attempt waits twice as long as the last
+fn deliver(receiver: Receiver<Job>, log: Arc<Mutex<VecDeque<Delivery>>>) {
+    let mut waiting: Vec<Job> = vec![];
+
+    loop {
+        let next_due = waiting.iter().map(|job| job.due).min();
+        let received = match next_due {
+            Some(due) => receiver.recv_timeout(due.saturating_duration_since(Instant::now())),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(job) => waiting.push(job),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let now = Instant::now();
+        let (due, later): (Vec<Job>, Vec<Job>) = waiting.drain(..).partition(|job| job.due <= now);
+        waiting = later;
+
+        for mut job in due {
+            job.attempts += 1;
+            let status = match post(&job.url, &job.body) {
+                Ok(code) if (200..300).contains(&code) => DeliveryStatus::Delivered(code),
+                // The request itself is wrong, sending it again will not help
+                Ok(code) if (400..500).contains(&code) && code != 429 => {
+                    DeliveryStatus::Failed(format!("HTTP {}", code))
+                }
+                Ok(code) => DeliveryStatus::Retrying(format!("HTTP {}", code)),
+                Err(e) => DeliveryStatus::Retrying(e),
+            };
+            let status = match status {
+                DeliveryStatus::Retrying(e) if job.attempts >= MAX_ATTEMPTS => {
+                    DeliveryStatus::Failed(e)
+                }
+                status => status,
+            };
+
+            if let Ok(mut log) = log.lock()
+                && let Some(delivery) = log.iter_mut().find(|v| v.id == job.id)
+            {
+                delivery.attempts = job.attempts;
+                delivery.status = status.clone();
+            }
+
+            if let DeliveryStatus::Retrying(_) = status {
+                job.due = Instant::now() + FIRST_RETRY * 2u32.pow(job.attempts - 1);
+                waiting.push(job);
+            }
+        }
+    }
+}
+
+// Plain HTTP/1.1, returns the status code
+fn post(url: &str, body: &str) -> Result<u16, String> {
+    let (host, port, path) = parse_url(url)?;
+    let address = (host.as_str(), port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("{} did not resolve", host))?;
+
+    let mut stream = TcpStream::connect_timeout(&address, REQUEST_TIMEOUT)
+        .map_err(|e| format!("Could not connect to {}: {}", host, e))?;
+    stream
+        .set_read_timeout(Some(REQUEST_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    stream
+        .set_write_timeout(Some(REQUEST_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nUser-Agent: interval-timer\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        port,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut status_line)
+        .map_err(|e| e.to_string())?;
+    // e.g. "HTTP/1.1 204 No Content"
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("Unexpected response '{}'", status_line.trim()))
+}
+
+// e.g. "http://localhost:8080/workouts" into host, port and path
+pub fn parse_url(url: &str) -> Result<(String, u16, String), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("Only http:// URLs are supported, got '{}'", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse::<u16>()
+                .map_err(|e| format!("Invalid port in '{}': {}", url, e))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("Missing host in '{}'", url));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+// {events};{url};{template}, the template goes last as it may contain anything
+fn parse_webhook(name: &str, value: &str) -> Result<Webhook, String> {
+    let mut fields = value.splitn(3, ';');
+    let events = fields
+        .next()
+        .unwrap_or_default()
+        .split('|')
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse::<WebhookEvent>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let url = fields
+        .next()
+        .ok_or_else(|| format!("Missing URL for webhook '{}'", name))?;
+
+    Ok(Webhook {
+        name: sanitize_name(name),
+        url: url.to_string(),
+        events,
+        template: fields.next().unwrap_or_default().to_string(),
+    })
+}
+
+fn webhook_to_string(webhook: &Webhook) -> String {
+    let events = webhook
+        .events
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join("|");
+    // One line per webhook in the file
+    let template = webhook.template.replace(['\r', '\n'], " ");
+    format!("{};{};{}", events, webhook.url, template)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::process;
+
+    use super::*;
+
+    // Answers every request with the given status, or not at all
+    fn stand_in(status: Option<u16>) -> (String, Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (sender, requests) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut held = vec![];
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let Some(status) = status else {
+                    // Never answers, the connection stays open until the test is over
+                    held.push(stream);
+                    continue;
+                };
+
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let response = format!("HTTP/1.1 {} Whatever\r\nContent-Length: 0\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).unwrap();
+                let _ = sender.send(String::from_utf8(body).unwrap());
+            }
+        });
+        (url, requests)
+    }
+
+    fn webhooks(name: &str) -> Webhooks {
+        let path = std::env::temp_dir().join(format!(
+            "interval-timer-webhooks-{}-{}",
+            process::id(),
+            name
+        ));
+        let _ = fs::remove_file(&path);
+        Webhooks::new_from_file(path.to_str().unwrap()).unwrap()
+    }
+
+    fn hook(name: &str, url: &str) -> Webhook {
+        Webhook {
+            name: name.to_string(),
+            url: url.to_string(),
+            ..Webhook::default()
+        }
+    }
+
+    fn wait_for_status(webhooks: &Webhooks, name: &str) -> DeliveryStatus {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let status = webhooks
+                .log()
+                .into_iter()
+                .find(|delivery| delivery.webhook == name)
+                .map(|delivery| delivery.status);
+            match status {
+                Some(DeliveryStatus::Pending) | None => {}
+                Some(status) => return status,
+            }
+            assert!(Instant::now() < deadline, "no delivery for {}", name);
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn posts_the_payload() {
+        let (url, requests) = stand_in(Some(204));
+        let mut webhooks = webhooks("posts");
+        webhooks.save(hook("log", &url)).unwrap();
+
+        let fields = WebhookFields {
+            label: "Rest".to_string(),
+            index: 2,
+            count: 3,
+            ..WebhookFields::default()
+        };
+        webhooks.fire(WebhookEvent::IntervalFinish, &fields);
+
+        let body = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(body.contains("\"event\":\"interval_finish\""), "{}", body);
+        assert!(body.contains("\"label\":\"Rest\""), "{}", body);
+        assert!(body.contains("\"index\":2"), "{}", body);
+        assert!(body.contains("\"missed\":false"), "{}", body);
+        assert_eq!(
+            wait_for_status(&webhooks, "log"),
+            DeliveryStatus::Delivered(204)
+        );
+    }
+
+    #[test]
+    fn gives_up_on_client_errors() {
+        let (url, _requests) = stand_in(Some(404));
+        let mut webhooks = webhooks("client-errors");
+        webhooks.save(hook("missing", &url)).unwrap();
+        webhooks.test("missing");
+
+        let status = wait_for_status(&webhooks, "missing");
+        assert_eq!(status, DeliveryStatus::Failed("HTTP 404".to_string()));
+        assert_eq!(webhooks.log()[0].attempts, 1);
+    }
+
+    #[test]
+    fn a_slow_endpoint_does_not_hold_up_the_others() {
+        let (slow_url, _) = stand_in(None);
+        let (fast_url, requests) = stand_in(Some(200));
+        let mut webhooks = webhooks("slow");
+        webhooks.save(hook("a-slow", &slow_url)).unwrap();
+        webhooks.save(hook("b-fast", &fast_url)).unwrap();
+
+        let started = Instant::now();
+        webhooks.fire(WebhookEvent::SessionStart, &WebhookFields::default());
+        requests.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        assert!(started.elapsed() < REQUEST_TIMEOUT);
+        assert_eq!(
+            wait_for_status(&webhooks, "b-fast"),
+            DeliveryStatus::Delivered(200)
+        );
+    }
+}
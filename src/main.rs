@@ -1,18 +1,69 @@
 #![windows_subsystem = "windows"]
 
 use eframe;
+use std::env;
 
 mod app;
-mod internal;
+use interval_timer::internal;
 mod ui;
 
 fn main() -> Result<(), String> {
+    let cli_args = internal::cli::CliArgs::parse(env::args())?;
+    if cli_args.help {
+        println!("{}", internal::cli::USAGE);
+        return Ok(());
+    }
+
+    // Hand the arguments to an instance that is already running
+    if let Ok(socket) = internal::dirs::default_socket_path() {
+        let args = internal::ipc::IpcRequest::Args(env::args().skip(1).collect());
+        if let Ok(reply) = internal::ipc::send_request(&socket, &args) {
+            return match reply.get("error").and_then(|v| v.as_str()) {
+                Some(error) => Err(error.to_string()),
+                None => Ok(()),
+            };
+        }
+    }
+
     let config_file = internal::dirs::default_config_path()?;
     let _ = internal::dirs::create_dirs_if_not_exists();
-    let app_options = internal::settings_parser::AppSettings::new_from_file(&config_file)?;
+    let mut app_options = internal::settings_parser::AppSettings::new_from_file(&config_file)?;
+    if let Some((sequence, timers)) = cli_args.sequence {
+        app_options.sequence = sequence;
+        app_options.timers = timers;
+        app_options.profile = String::new();
+    }
+    let history_file = internal::dirs::default_history_path()?;
+    let history = internal::history::History::new_from_file(&history_file)?;
+
+    let session_file = internal::dirs::default_session_path()?;
+    let session = internal::session::SessionState::new_from_file(&session_file).unwrap_or(None);
+
+    let templates_file = internal::dirs::default_templates_path()?;
+    let templates = internal::templates::TemplateLibrary::new_from_file(&templates_file)?;
+
+    let webhooks_file = internal::dirs::default_webhooks_path()?;
+    let webhooks = internal::webhooks::Webhooks::new_from_file(&webhooks_file)?;
 
-    let app = app::App::new(app_options);
-    let options = eframe::NativeOptions::default();
-    eframe::run_native("Interval Timer", options, Box::new(|_cc| Box::new(app)))
-        .map_err(|v| v.to_string())
+    let mut app = app::App::new(
+        app_options,
+        history,
+        session,
+        templates,
+        webhooks,
+        cli_args.kiosk,
+    );
+    let options = eframe::NativeOptions {
+        viewport: eframe::egui::ViewportBuilder::default().with_fullscreen(cli_args.kiosk),
+        ..Default::default()
+    };
+    eframe::run_native(
+        "Interval Timer",
+        options,
+        Box::new(|cc| {
+            app.start_ipc(&cc.egui_ctx);
+            Box::new(app)
+        }),
+    )
+    .map_err(|v| v.to_string())
 }
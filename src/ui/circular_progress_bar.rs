@@ -3,6 +3,8 @@ use eframe::egui;
 use egui::{Color32, Pos2, Shape, Stroke, Vec2};
 use std::f32::consts::{FRAC_PI_2, TAU};
 
+use crate::ui::theme;
+
 pub fn draw(
     ui: &mut egui::Ui,
     width: f32,
@@ -24,19 +26,168 @@ pub fn draw(
     draw_segments(&mut painter, center, radius, color, frac);
 
     if let Some(text) = text {
-        painter.text(
-            center,
-            egui::Align2::CENTER_CENTER,
-            text,
-            egui::FontId::proportional(32.0),
-            Color32::WHITE,
-        );
+        // The text sits across the center, mostly over the filled part once it passes half
+        let under = match frac > 0.5 {
+            true => color,
+            false => color.linear_multiply(0.05),
+        };
+        draw_text(&painter, center, radius, text, under);
     }
 
     let stroke_color = color.linear_multiply(0.05);
     painter.circle_stroke(center, radius, Stroke::new(2.0, stroke_color));
 }
 
+// Compact ring for small sizes, a stroked arc with a handful
+// of segments instead of the filled pie
+pub fn draw_thin(ui: &mut egui::Ui, size: f32, color: Color32, frac: f32) {
+    let desired_size = Vec2 { x: size, y: size };
+    let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::hover());
+
+    let thickness = (size * 0.12).max(2.0);
+    let center = response.rect.center();
+    let radius = size / 2.0 - thickness / 2.0;
+
+    let background = Stroke::new(thickness, color.linear_multiply(0.15));
+    painter.circle_stroke(center, radius, background);
+    draw_arc(
+        &painter,
+        center,
+        radius,
+        -FRAC_PI_2,
+        frac * TAU,
+        Stroke::new(thickness, color),
+    );
+}
+
+// Donut with a thickness relative to the radius
+pub fn draw_ring(
+    ui: &mut egui::Ui,
+    size: f32,
+    color: Color32,
+    frac: f32,
+    thickness: f32,
+    text: Option<&str>,
+) {
+    let desired_size = Vec2 { x: size, y: size };
+    let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::hover());
+
+    let center = response.rect.center();
+    let outer = size / 2.0 - 4.0;
+    let width = (outer * thickness).max(2.0);
+    let radius = outer - width / 2.0;
+
+    let background = Stroke::new(width, color.linear_multiply(0.05));
+    painter.circle_stroke(center, radius, background);
+    draw_arc(
+        &painter,
+        center,
+        radius,
+        -FRAC_PI_2,
+        frac * TAU,
+        Stroke::new(width, color),
+    );
+
+    if let Some(text) = text {
+        draw_text(&painter, center, outer, text, Color32::TRANSPARENT);
+    }
+}
+
+// The whole session around one ring, each interval taking a share of
+// the circle by its length, with the current one highlighted
+pub fn draw_session(
+    ui: &mut egui::Ui,
+    size: f32,
+    segments: &[(f32, Color32)],
+    current: usize,
+    frac: f32,
+    text: Option<&str>,
+) {
+    let desired_size = Vec2 { x: size, y: size };
+    let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::hover());
+
+    let center = response.rect.center();
+    let outer = size / 2.0 - 4.0;
+    let width = (outer * 0.18).max(2.0);
+    let radius = outer - width / 2.0;
+
+    let total: f32 = segments.iter().map(|(weight, _)| weight).sum();
+    let gap = match segments.len() {
+        0 | 1 => 0.0,
+        n => (0.02_f32).min(TAU / n as f32 * 0.2),
+    };
+
+    let mut angle = -FRAC_PI_2;
+    for (i, (weight, color)) in segments.iter().enumerate() {
+        let sweep = match total > 0.0 {
+            true => weight / total * TAU,
+            false => TAU / segments.len() as f32,
+        };
+        let visible = (sweep - gap).max(0.0);
+        let start = angle + gap / 2.0;
+
+        if i < current {
+            draw_arc(
+                &painter,
+                center,
+                radius,
+                start,
+                visible,
+                Stroke::new(width, *color),
+            );
+        } else if i == current {
+            let dim = Stroke::new(width * 1.3, color.linear_multiply(0.2));
+            draw_arc(&painter, center, radius, start, visible, dim);
+            let filled = Stroke::new(width * 1.3, *color);
+            draw_arc(&painter, center, radius, start, visible * frac, filled);
+        } else {
+            let dim = Stroke::new(width, color.linear_multiply(0.2));
+            draw_arc(&painter, center, radius, start, visible, dim);
+        }
+
+        angle += sweep;
+    }
+
+    if let Some(text) = text {
+        draw_text(&painter, center, outer, text, Color32::TRANSPARENT);
+    }
+}
+
+fn draw_text(painter: &egui::Painter, center: Pos2, radius: f32, text: &str, under: Color32) {
+    let style = painter.ctx().style();
+
+    // Keeps the text at 32pt for the classic 500x500 ring
+    let font_size = (radius * 0.13 * theme::timer_font_scale(&style)).max(12.0);
+    painter.text(
+        center,
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::FontId::proportional(font_size),
+        theme::text_color_on(&style, under),
+    );
+}
+
+fn draw_arc(
+    painter: &egui::Painter,
+    center: Pos2,
+    radius: f32,
+    start_angle: f32,
+    sweep_angle: f32,
+    stroke: Stroke,
+) {
+    const SEGMENTS_PER_TURN: f32 = 64.0;
+
+    let segments = (SEGMENTS_PER_TURN * sweep_angle / TAU).ceil() as usize;
+    if segments == 0 {
+        return;
+    }
+
+    let arc: Vec<Pos2> = (0..=segments)
+        .map(|i| make_segment(start_angle, sweep_angle, i, segments, center, radius))
+        .collect();
+    painter.add(Shape::line(arc, stroke));
+}
+
 pub fn draw_segments(
     painter: &mut egui::Painter,
     center: Pos2,
@@ -1,29 +1,450 @@
 use crate::ui::circular_progress_bar;
+use crate::ui::effects::{ease_out, lerp_color};
+use crate::ui::format::format_duration;
+use crate::ui::progress::{self, Progress};
 use eframe::egui;
 use egui::Color32;
+use std::time::{Duration, Instant};
 
-use crate::internal::clock::{Clock, State};
+use crate::internal::clock::{Clock, Now, State};
 use crate::internal::queue::Queue;
+use crate::internal::random::Rng;
+use crate::internal::settings_parser::{CatchUp, ProgressStyle, ShuffleSettings};
+use crate::internal::shuffle;
+use crate::internal::timer::{Advance, Timer, TimerKind};
+use crate::internal::title::TitleFields;
+
+struct Interval {
+    clock: Clock,
+    color: Color32,
+    kind: TimerKind,
+    label: String,
+    laps: Vec<Duration>,
+    advance: Advance,
+    // Length before any quick adjustments, restored when the interval starts again
+    planned: Duration,
+    adjustment_ms: i64,
+}
 
 pub struct CountdownElement {
-    timers: Queue<(Clock, Color32)>,
+    timers: Queue<Interval>,
+    // Configured timers that each shuffled round is dealt from
+    pool: Vec<Timer>,
+    shuffle: ShuffleSettings,
+    rng: Rng,
+    // Rounds played before and, after stepping back, after the current one
+    played: Vec<Vec<Timer>>,
+    upcoming: Vec<Vec<Timer>>,
     play_once: bool,
+    // Held at the start of an interval until the user presses Start
+    ready: bool,
+    // Scrolled on the ring but not yet enough for a whole step
+    scroll: f32,
+    round: u32,
+    catch_up: CatchUp,
+    last_frame: Instant,
+    drift: DriftReport,
+    now: Now,
+    style: ProgressStyle,
+    ring_thickness: f32,
+    animation: Option<Duration>,
+    transition: Option<Transition>,
+}
+
+// Where the ring was drawn when the interval changed
+struct Transition {
+    started: Instant,
+    color: Color32,
+    frac: f32,
+}
+
+// Frames further apart than this mean the window was hidden
+// or the system was suspended, rather than regular ticking
+const GAP_THRESHOLD: Duration = Duration::from_secs(1);
+const MAX_CATCH_UP: usize = 10_000;
+// How many shuffled rounds Previous can step back through
+const MAX_PLAYED_ROUNDS: usize = 100;
+
+const MIN_RING_SIZE: f32 = 120.0;
+const READY_TEXT: &str = "Ready?";
+pub const ADD_TIME: Duration = Duration::from_secs(30);
+pub const REMOVE_TIME: Duration = Duration::from_secs(10);
+// Each notch scrolled on the ring changes the time by this much
+const SCROLL_STEP: Duration = Duration::from_secs(10);
+const SCROLL_NOTCH: f32 = 40.0;
+// Share of the session ring given to intervals without a fixed length
+const COUNT_UP_WEIGHT: f32 = 60.0;
+// Room left under the ring for the buttons in the regular view
+const CONTROLS_HEIGHT: f32 = 104.0;
+
+#[derive(Default, Debug)]
+pub struct DriftReport {
+    pub transitions: u32,
+    // How late each transition was noticed, chaining keeps it out of the schedule
+    pub total_latency: Duration,
+    pub max_latency: Duration,
+}
+
+#[derive(Debug)]
+pub struct IntervalSummary {
+    // Where the interval sat when it ended
+    pub index: usize,
+    pub round: u32,
+    pub duration: Duration,
+    pub elapsed: Duration,
+    pub color: Color32,
+    pub kind: TimerKind,
+    pub label: String,
+    pub laps: Vec<Duration>,
+    // Net change made with the quick adjust controls
+    pub adjustment_ms: i64,
+    // Set when moving on from it wrapped back to the first interval
+    pub completed_round: bool,
+}
+
+pub struct IntervalInfo {
+    pub label: String,
+    pub color: Color32,
+    pub kind: TimerKind,
+    pub duration: Duration,
+}
+
+pub struct SessionOverview {
+    pub intervals: Vec<IntervalInfo>,
+    pub current: usize,
+    pub round: u32,
+    pub elapsed: Duration,
+    pub remaining: Duration,
+    // Open-ended intervals make the remaining time a lower bound
+    pub open_ended: bool,
+    pub running: bool,
 }
 
 #[derive(Debug)]
 pub enum CountdownSignal {
     // Types of events that can be returned
-    Finished,
+    Finished(IntervalSummary),
+    Missed(IntervalSummary),
+    Skipped(IntervalSummary),
+    // Asks to lengthen or, when negative, shorten the current interval
+    Adjust(i64),
+}
+
+impl Interval {
+    fn new(timer: &Timer, now: Now) -> Self {
+        Self {
+            clock: match timer.counts_up() {
+                true => Clock::new_count_up_with(now),
+                false => Clock::new_with(timer.millis, now),
+            },
+            color: Color32::from_rgb(timer.color.0, timer.color.1, timer.color.2),
+            kind: timer.kind,
+            label: timer.label.clone(),
+            laps: vec![],
+            advance: timer.advance,
+            planned: Duration::from_millis(timer.millis),
+            adjustment_ms: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.clock.reset();
+        if !self.clock.counts_up() {
+            self.clock.set_duration(self.planned);
+        }
+        self.laps.clear();
+        self.adjustment_ms = 0;
+    }
+
+    fn to_timer(&self) -> Timer {
+        let [r, g, b, _] = self.color.to_array();
+        Timer {
+            millis: self.planned.as_millis() as u64,
+            color: (r, g, b),
+            kind: self.kind,
+            label: self.label.clone(),
+            advance: self.advance,
+        }
+    }
 }
 
 impl CountdownElement {
-    pub fn new(timers: &Vec<(u64, Color32)>, play_once: bool) -> Self {
+    pub fn new(timers: &[Timer], play_once: bool, catch_up: CatchUp) -> Self {
+        Self::new_with(timers, play_once, catch_up, Instant::now)
+    }
+
+    pub fn new_with(timers: &[Timer], play_once: bool, catch_up: CatchUp, now: Now) -> Self {
         let mut queue = Queue::new();
-        queue.set(timers.iter().map(|v| (Clock::new(v.0), v.1)).collect());
+        queue.set(
+            timers
+                .iter()
+                .map(|timer| Interval::new(timer, now))
+                .collect(),
+        );
 
         Self {
             timers: queue,
+            pool: timers.to_vec(),
+            shuffle: ShuffleSettings::default(),
+            rng: Rng::from_time(),
+            played: vec![],
+            upcoming: vec![],
             play_once: play_once,
+            ready: false,
+            scroll: 0.0,
+            round: 0,
+            catch_up,
+            last_frame: now(),
+            drift: DriftReport::default(),
+            now,
+            style: ProgressStyle::default(),
+            ring_thickness: 0.25,
+            animation: None,
+            transition: None,
+        }
+    }
+
+    // The round being played when it was dealt rather than configured
+    pub fn dealt_round(&self) -> Option<Vec<Timer>> {
+        self.shuffle.is_active().then(|| self.intervals())
+    }
+
+    // Plays a round dealt earlier, e.g. by the session being resumed
+    pub fn restore_round(&mut self, round: &[Timer]) {
+        if self.shuffle.is_active() && !round.is_empty() {
+            self.set_round(round, 0);
+        }
+    }
+
+    // Deals the first round again, so call it before the timer has run
+    pub fn set_shuffle(&mut self, shuffle: ShuffleSettings) {
+        self.shuffle = shuffle;
+        self.rng = match shuffle.seed {
+            Some(seed) => Rng::new(seed),
+            None => Rng::from_time(),
+        };
+        self.played.clear();
+        self.upcoming.clear();
+        if shuffle.is_active() {
+            let round = shuffle::deal(&self.pool, &self.shuffle, &mut self.rng);
+            self.set_round(&round, 0);
+        }
+    }
+
+    // Both apply from here on, the session carries on
+    pub fn set_play_once(&mut self, play_once: bool) {
+        self.play_once = play_once;
+    }
+
+    pub fn set_catch_up(&mut self, catch_up: CatchUp) {
+        self.catch_up = catch_up;
+    }
+
+    pub fn set_animation(&mut self, animation: Option<Duration>) {
+        self.animation = animation;
+        self.transition = None;
+    }
+
+    pub fn color(&self) -> Color32 {
+        self.get_color()
+    }
+
+    // Time left in a running countdown, open-ended intervals have none
+    pub fn remaining(&self) -> Option<Duration> {
+        let clock = self.get_clock()?;
+        match clock.is_running() && !clock.counts_up() {
+            true => Some(clock.remaining()),
+            false => None,
+        }
+    }
+
+    pub fn set_style(&mut self, style: ProgressStyle, ring_thickness: f32) {
+        self.style = style;
+        self.ring_thickness = ring_thickness;
+    }
+
+    pub fn index(&self) -> usize {
+        self.timers.index()
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.get_clock()
+            .map(|clock| clock.elapsed())
+            .unwrap_or(Duration::ZERO)
+    }
+
+    pub fn drift_report(&self) -> &DriftReport {
+        &self.drift
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.get_clock().is_none_or(|clock| !clock.is_running())
+    }
+
+    // Waiting at an interval that does not start on its own
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    pub fn restore(&mut self, index: usize, round: u32, elapsed: Duration, paused: bool) -> bool {
+        if !self.timers.set_index(index) {
+            return false;
+        }
+        self.ready = false;
+
+        self.round = round;
+        if let Some(clock) = self.get_clock_mut() {
+            clock.restore(elapsed, !paused);
+        }
+        true
+    }
+
+    pub fn toggle_running(&mut self) {
+        self.ready = false;
+        let running = self.is_running();
+        if let Some(clock) = self.get_clock_mut() {
+            match running {
+                true => clock.stop(),
+                false => clock.start(),
+            }
+        }
+    }
+
+    pub fn previous(&mut self) -> Option<CountdownSignal> {
+        let signal = self.skipped_summary(false);
+        self.prev();
+        if let Some(clock) = self.get_clock_mut() {
+            clock.stop();
+        }
+        signal
+    }
+
+    pub fn skip(&mut self) -> Option<CountdownSignal> {
+        // Ending a running open-ended interval moves straight on
+        let keep_running = self.counts_up() && self.is_running();
+        let mut signal = self.skipped_summary(true);
+        let wrapped = self.next();
+        if let Some(CountdownSignal::Finished(summary)) = &mut signal {
+            summary.completed_round = wrapped;
+        }
+        if !keep_running && let Some(clock) = self.get_clock_mut() {
+            clock.stop();
+        }
+        signal
+    }
+
+    pub fn jump_to(&mut self, index: usize) -> Option<CountdownSignal> {
+        let running = self.is_running();
+        let signal = self.skipped_summary(false);
+        self.begin_transition();
+        if !self.timers.set_index(index) {
+            return None;
+        }
+
+        self.enter_interval(false);
+        if !running && let Some(clock) = self.get_clock_mut() {
+            clock.stop();
+        }
+        signal
+    }
+
+    pub fn session_overview(&self) -> SessionOverview {
+        let current = self.timers.index();
+        let current_elapsed = self
+            .summary()
+            .map(|summary| summary.elapsed)
+            .unwrap_or(Duration::ZERO);
+
+        let intervals: Vec<IntervalInfo> = self
+            .timers
+            .items()
+            .iter()
+            .map(|interval| IntervalInfo {
+                label: interval.label.clone(),
+                color: interval.color,
+                kind: interval.kind,
+                duration: interval.clock.duration(),
+            })
+            .collect();
+
+        let before: Duration = intervals.iter().take(current).map(|v| v.duration).sum();
+        let after: Duration = intervals.iter().skip(current + 1).map(|v| v.duration).sum();
+        let current_remaining = self
+            .get_clock()
+            .map(|clock| clock.remaining())
+            .unwrap_or(Duration::ZERO);
+
+        SessionOverview {
+            open_ended: intervals
+                .iter()
+                .skip(current)
+                .any(|v| v.kind != TimerKind::Countdown),
+            intervals,
+            current,
+            round: self.round,
+            elapsed: before + current_elapsed,
+            remaining: current_remaining + after,
+            running: self.is_running(),
+        }
+    }
+
+    pub fn title_fields(&self) -> Option<TitleFields> {
+        let interval = self.timers.get()?;
+        Some(TitleFields {
+            remaining: interval.clock.remaining(),
+            elapsed: interval.clock.elapsed(),
+            counts_up: interval.clock.counts_up(),
+            label: interval.label.clone(),
+            index: self.timers.index(),
+            count: self.timers.items().len(),
+            round: self.round,
+            fraction: interval.clock.fraction(),
+            running: interval.clock.is_running(),
+        })
+    }
+
+    // The sequence as it stands, including anything inserted since loading
+    pub fn intervals(&self) -> Vec<Timer> {
+        self.timers.items().iter().map(Interval::to_timer).collect()
+    }
+
+    // Runs after the current interval, and in every later round
+    // unless the rounds are shuffled
+    pub fn insert_next(&mut self, timer: &Timer) {
+        self.timers.insert_next(Interval::new(timer, self.now));
+    }
+
+    // Lengthens or, with a negative amount, shortens the current countdown
+    // and returns its new length. Shortening never ends it before now, so
+    // the next interval does not start partway through. Kept changes last
+    // beyond this run of the interval
+    pub fn adjust(&mut self, millis: i64, keep: bool) -> Option<Duration> {
+        let interval = self.timers.get_mut()?;
+        if interval.clock.counts_up() {
+            return None;
+        }
+
+        let before = interval.clock.duration();
+        let change = Duration::from_millis(millis.unsigned_abs());
+        let duration = match millis < 0 {
+            true => before.saturating_sub(change).max(interval.clock.elapsed()),
+            false => before + change,
+        };
+        interval.clock.set_duration(duration);
+        interval.adjustment_ms += duration.as_millis() as i64 - before.as_millis() as i64;
+        if keep {
+            interval.planned = duration;
+        }
+        Some(duration)
+    }
+
+    pub fn restart(&mut self) {
+        if let Some(interval) = self.timers.get_mut() {
+            interval.reset();
         }
     }
 
@@ -34,7 +455,7 @@ impl CountdownElement {
             return None;
         }
 
-        Some(&timer.unwrap().0)
+        Some(&timer.unwrap().clock)
     }
 
     fn get_clock_mut(&mut self) -> Option<&mut Clock> {
@@ -44,7 +465,7 @@ impl CountdownElement {
             return None;
         }
 
-        Some(&mut timer.unwrap().0)
+        Some(&mut timer.unwrap().clock)
     }
 
     fn get_color(&self) -> Color32 {
@@ -54,7 +475,7 @@ impl CountdownElement {
             return Color32::from_rgb(255, 255, 0);
         }
 
-        timer.unwrap().1
+        timer.unwrap().color
     }
 
     fn get_state(&self) -> State {
@@ -64,49 +485,324 @@ impl CountdownElement {
             return State::Stopped;
         }
 
-        timer.unwrap().0.get_state()
+        timer.unwrap().clock.get_state()
     }
 
     fn is_running(&self) -> bool {
         self.get_state() == State::Running
     }
 
-    fn is_finished(&self) -> bool {
-        self.get_state() == State::Finished
+    fn counts_up(&self) -> bool {
+        self.get_clock().is_some_and(|clock| clock.counts_up())
+    }
+
+    pub fn summary(&self) -> Option<IntervalSummary> {
+        let interval = self.timers.get()?;
+        let elapsed = match interval.clock.counts_up() {
+            true => interval.clock.elapsed(),
+            false => interval.clock.elapsed().min(interval.clock.duration()),
+        };
+
+        Some(IntervalSummary {
+            index: self.timers.index(),
+            round: self.round,
+            duration: interval.clock.duration(),
+            elapsed,
+            color: interval.color,
+            kind: interval.kind,
+            label: interval.label.clone(),
+            laps: interval.laps.clone(),
+            adjustment_ms: interval.adjustment_ms,
+            completed_round: false,
+        })
+    }
+
+    // Open-ended intervals are meant to be ended by hand, so ending one
+    // with Next finishes it. Stepping back or jumping away only leaves it
+    fn skipped_summary(&self, ended: bool) -> Option<CountdownSignal> {
+        let summary = self
+            .summary()
+            .filter(|summary| summary.elapsed.as_millis() > 0)?;
+
+        match ended && summary.kind != TimerKind::Countdown {
+            true => Some(CountdownSignal::Finished(summary)),
+            false => Some(CountdownSignal::Skipped(summary)),
+        }
+    }
+
+    fn lap(&mut self) {
+        if let Some(interval) = self.timers.get_mut() {
+            let elapsed = interval.clock.elapsed();
+            let previous: Duration = interval.laps.iter().sum();
+            interval.laps.push(elapsed.saturating_sub(previous));
+        }
+    }
+
+    fn begin_transition(&mut self) {
+        if self.animation.is_none() {
+            return;
+        }
+
+        let frac = self
+            .get_clock()
+            .map(|clock| clock.fraction())
+            .unwrap_or(0.0);
+        self.transition = Some(Transition {
+            started: (self.now)(),
+            color: self.get_color(),
+            frac,
+        });
+    }
+
+    fn set_round(&mut self, timers: &[Timer], index: usize) {
+        let now = self.now;
+        self.timers.set(
+            timers
+                .iter()
+                .map(|timer| Interval::new(timer, now))
+                .collect(),
+        );
+        self.timers.set_index(index);
     }
 
+    // Shuffled rounds step back through the order that was played
     fn prev(&mut self) {
-        self.timers.prev();
-        if let Some(clock) = self.get_clock_mut() {
-            clock.reset();
+        self.begin_transition();
+        let wraps = self.timers.is_first();
+        if wraps {
+            self.round = self.round.saturating_sub(1);
+        }
+        match wraps && self.shuffle.is_active() {
+            true => {
+                if let Some(round) = self.played.pop() {
+                    self.upcoming.push(self.intervals());
+                    self.set_round(&round, round.len().saturating_sub(1));
+                }
+            }
+            false => self.timers.prev(),
         }
+        self.enter_interval(false);
     }
 
-    fn next(&mut self) {
-        let play_once_pause_condition = self.play_once && self.timers.is_last();
-        self.timers.next();
-        if let Some(clock) = self.get_clock_mut() {
-            clock.reset();
-            if play_once_pause_condition {
-                clock.stop();
+    // Holds the next interval when the finished one waits for confirmation,
+    // the next one waits to begin, or a single run has come to an end.
+    // Returns whether the round wrapped around
+    fn next(&mut self) -> bool {
+        self.begin_transition();
+        let wraps = self.timers.is_last();
+        let confirm = self
+            .timers
+            .get()
+            .is_some_and(|interval| interval.advance == Advance::Confirm);
+        let gate = confirm || (self.play_once && wraps);
+        if wraps {
+            self.round += 1;
+        }
+        match wraps && self.shuffle.is_active() {
+            true => {
+                self.played.push(self.intervals());
+                if self.played.len() > MAX_PLAYED_ROUNDS {
+                    self.played.remove(0);
+                }
+                let round = match self.upcoming.pop() {
+                    Some(round) => round,
+                    None => shuffle::deal(&self.pool, &self.shuffle, &mut self.rng),
+                };
+                self.set_round(&round, 0);
+            }
+            false => self.timers.next(),
+        }
+        self.enter_interval(gate);
+        wraps
+    }
+
+    // Starts the interval that is now current over, holding it for Start
+    // when asked to or when it waits to begin
+    fn enter_interval(&mut self, hold: bool) {
+        self.ready = false;
+        if let Some(interval) = self.timers.get_mut() {
+            interval.reset();
+            if hold || interval.advance == Advance::Hold {
+                interval.clock.stop();
+                self.ready = true;
+            }
+        }
+    }
+
+    fn is_finished_at(&self, now: Instant) -> bool {
+        self.timers
+            .get()
+            .is_some_and(|timer| timer.clock.get_state_at(now) == State::Finished)
+    }
+
+    // Moves on from a finished interval, starting the next one at the
+    // exact instant the previous one ended instead of when it was noticed
+    fn advance(&mut self, now: Instant) -> Option<IntervalSummary> {
+        let deadline = self.get_clock()?.deadline()?;
+        let mut summary = self.summary()?;
+
+        let latency = now.saturating_duration_since(deadline);
+        self.drift.transitions += 1;
+        self.drift.total_latency += latency;
+        self.drift.max_latency = self.drift.max_latency.max(latency);
+
+        summary.completed_round = self.next();
+        if let Some(clock) = self.get_clock_mut()
+            && clock.is_running()
+        {
+            clock.reset_at(deadline);
+        }
+
+        Some(summary)
+    }
+
+    fn catch_up(&mut self, gap: Duration) -> Vec<CountdownSignal> {
+        if self.catch_up == CatchUp::PauseOnResume {
+            // Freeze the clock where it stood before the gap
+            if let Some(clock) = self.get_clock_mut()
+                && clock.is_running()
+            {
+                let elapsed = clock.elapsed().saturating_sub(gap);
+                clock.restore(elapsed, false);
             }
+            return vec![];
         }
+
+        let now = (self.now)();
+        let mut missed = vec![];
+        let mut round_deadline = None;
+        while self.is_finished_at(now) && missed.len() < MAX_CATCH_UP {
+            // A round of zero-length intervals ends where it began
+            if self.timers.index() == 0 {
+                let deadline = self.get_clock().and_then(|clock| clock.deadline());
+                if round_deadline.is_some() && round_deadline == deadline {
+                    break;
+                }
+                round_deadline = deadline;
+            }
+            let Some(summary) = self.advance(now) else {
+                break;
+            };
+            missed.push(summary);
+        }
+
+        let last = match self.catch_up {
+            CatchUp::PlayOneChime => missed.pop(),
+            _ => None,
+        };
+
+        missed
+            .into_iter()
+            .map(CountdownSignal::Missed)
+            .chain(last.map(CountdownSignal::Finished))
+            .collect()
     }
 
-    fn draw_progress(&self, ui: &mut egui::Ui, clock: &Clock, color: Color32) {
+    pub fn next_up(&self) -> String {
+        if self.play_once && self.timers.is_last() {
+            return "Last interval".to_string();
+        }
+
+        let Some(interval) = self.timers.peek_next() else {
+            return String::new();
+        };
+
+        let duration = match interval.clock.counts_up() {
+            true => interval.kind.label().to_string(),
+            false => format_duration(interval.clock.duration()),
+        };
+
+        match interval.label.is_empty() {
+            true => format!("Next: {}", duration),
+            false => format!("Next: {} {}", interval.label, duration),
+        }
+    }
+
+    fn draw_progress(
+        &self,
+        ui: &mut egui::Ui,
+        clock: &Clock,
+        color: Color32,
+        size: f32,
+    ) -> egui::Rect {
         let frac = clock.fraction();
         let timestamp = &clock.to_string();
 
+        // Open-ended intervals pulse so they read differently from a countdown
+        let color = match clock.counts_up() && clock.is_running() {
+            true => {
+                let phase = clock.elapsed().as_secs_f32() * std::f32::consts::TAU;
+                color.linear_multiply(0.75 + 0.25 * phase.cos())
+            }
+            false => color,
+        };
+
+        // Blend from where the previous interval left off
+        let (color, frac) = match (&self.transition, self.animation) {
+            (Some(transition), Some(animation)) if !animation.is_zero() => {
+                let elapsed = (self.now)().saturating_duration_since(transition.started);
+                let t = elapsed.div_duration_f32(animation);
+                let t = ease_out(t.min(1.0));
+                (
+                    lerp_color(transition.color, color, t),
+                    transition.frac + (frac - transition.frac) * t,
+                )
+            }
+            _ => (color, frac),
+        };
+
+        let segments: Vec<(f32, Color32)> = self
+            .timers
+            .items()
+            .iter()
+            .map(|interval| {
+                let weight = match interval.clock.counts_up() {
+                    true => COUNT_UP_WEIGHT,
+                    false => interval.clock.duration().as_secs_f32(),
+                };
+                (weight, interval.color)
+            })
+            .collect();
+
+        let progress = Progress {
+            color,
+            frac,
+            text: timestamp,
+            ring_thickness: self.ring_thickness,
+            segments: &segments,
+            current: self.timers.index(),
+        };
+
         ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-            circular_progress_bar::draw(ui, 500.0, 500.0, color, frac, Some(timestamp));
-        });
+            progress::draw(ui, self.style, size, &progress);
+        })
+        .response
+        .rect
+    }
+
+    // Scrolling up over the ring adds time, scrolling down takes it away
+    fn scroll_adjustment(&mut self, ui: &egui::Ui, rect: egui::Rect) -> Option<CountdownSignal> {
+        if !ui.rect_contains_pointer(rect) || self.counts_up() {
+            self.scroll = 0.0;
+            return None;
+        }
+
+        self.scroll += ui.input(|i| i.raw_scroll_delta.y);
+        let notches = (self.scroll / SCROLL_NOTCH).trunc();
+        if notches == 0.0 {
+            return None;
+        }
+        self.scroll -= notches * SCROLL_NOTCH;
+        let millis = notches as i64 * SCROLL_STEP.as_millis() as i64;
+        Some(CountdownSignal::Adjust(millis))
     }
 
-    fn draw_placeholder(&self, ui: &mut egui::Ui, color: Color32) {
-        circular_progress_bar::draw(ui, 500.0, 500.0, color, 0.0, Some("No Timers Set"));
+    fn draw_placeholder(&self, ui: &mut egui::Ui, color: Color32, size: f32) {
+        circular_progress_bar::draw(ui, size, size, color, 0.0, Some("No Timers Set"));
     }
 
-    fn draw_buttons(&mut self, ui: &mut egui::Ui) {
+    fn draw_buttons(&mut self, ui: &mut egui::Ui) -> Option<CountdownSignal> {
+        let mut signal = None;
         let layout = egui::Layout::centered_and_justified(egui::Direction::LeftToRight);
 
         ui.allocate_ui_with_layout([240., 40.].into(), layout, |ui| {
@@ -114,52 +810,449 @@ impl CountdownElement {
                 ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
                     let button = egui::Button::new("<").min_size([60., 40.].into());
                     if ui.add_sized([60., 40.], button).clicked() {
-                        self.prev();
-                        self.get_clock_mut().unwrap().stop();
+                        signal = self.previous();
                     }
 
-                    if self.is_running() {
-                        let button = egui::Button::new("Stop").min_size([120., 40.].into());
-                        if ui.add(button).clicked() {
-                            self.get_clock_mut().unwrap().stop();
-                        }
-                    } else {
-                        let button = egui::Button::new("Start").min_size([120., 40.].into());
-                        if ui.add(button).clicked() {
-                            self.get_clock_mut().unwrap().start();
-                        }
+                    let text = match (self.is_running(), self.ready) {
+                        (true, _) => "Pause",
+                        (false, true) => "Go",
+                        (false, false) => "Start",
                     };
+                    let button = egui::Button::new(text).min_size([120., 40.].into());
+                    if ui.add(button).clicked() {
+                        self.toggle_running();
+                    }
 
                     let button = egui::Button::new(">").min_size([60., 40.].into());
                     if ui.add_sized([60., 40.], button).clicked() {
-                        self.next();
-                        self.get_clock_mut().unwrap().stop();
+                        signal = self.skip();
                     }
                 });
             });
         });
+
+        if !self.counts_up() {
+            ui.add_space(8.);
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 128.).max(0.) / 2.);
+                let text = format!("-{}s", REMOVE_TIME.as_secs());
+                let button = egui::Button::new(text).min_size([60., 24.].into());
+                if ui.add(button).clicked() {
+                    signal = Some(CountdownSignal::Adjust(-(REMOVE_TIME.as_millis() as i64)));
+                }
+                let text = format!("+{}s", ADD_TIME.as_secs());
+                let button = egui::Button::new(text).min_size([60., 24.].into());
+                if ui.add(button).clicked() {
+                    signal = Some(CountdownSignal::Adjust(ADD_TIME.as_millis() as i64));
+                }
+            });
+        }
+
+        if self
+            .timers
+            .get()
+            .is_some_and(|v| v.kind == TimerKind::Stopwatch)
+        {
+            self.draw_laps(ui);
+        }
+
+        signal
     }
 
-    pub fn draw(&mut self, ui: &mut egui::Ui) -> Option<CountdownSignal> {
-        // Checking before rendering, prevents
-        // displayed state from flickering
-        let signal = if self.is_finished() {
-            self.next();
-            Some(CountdownSignal::Finished)
-        } else {
-            None
+    fn draw_laps(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(8.);
+        let button = egui::Button::new("Lap").min_size([120., 30.].into());
+        if ui.add_enabled(self.is_running(), button).clicked() {
+            self.lap();
+        }
+
+        let Some(interval) = self.timers.get() else {
+            return;
         };
 
+        for (i, lap) in interval.laps.iter().enumerate().rev() {
+            ui.label(format!("Lap {}: {:.2}s", i + 1, lap.as_secs_f32()));
+        }
+    }
+
+    // Call once per frame, whichever view is showing, so a gap
+    // means the app really was not running
+    pub fn tick(&mut self) -> Vec<CountdownSignal> {
+        let now = (self.now)();
+        let gap = now - self.last_frame;
+        self.last_frame = now;
+
+        if gap > GAP_THRESHOLD {
+            self.catch_up(gap)
+        } else if self.is_finished_at(now) {
+            let summary = self.advance(now);
+            summary.map(CountdownSignal::Finished).into_iter().collect()
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui) -> Vec<CountdownSignal> {
+        let mut signals = vec![];
+
         ui.vertical_centered(|ui| {
+            if let Some(interval) = self.timers.get()
+                && !interval.label.is_empty()
+            {
+                ui.heading(&interval.label);
+            }
+
+            let available = ui.available_size();
+            let size = available
+                .x
+                .min(available.y - CONTROLS_HEIGHT)
+                .max(MIN_RING_SIZE);
+
             if let Some(clock) = self.get_clock() {
-                self.draw_progress(ui, clock, self.get_color());
+                let rect = self.draw_progress(ui, clock, self.get_color(), size);
+                signals.extend(self.scroll_adjustment(ui, rect));
                 ui.add_space(16.);
-                self.draw_buttons(ui);
+                if self.ready {
+                    ui.label(egui::RichText::new(READY_TEXT).size(24.).strong());
+                }
+                signals.extend(self.draw_buttons(ui));
             } else {
-                self.draw_placeholder(ui, self.get_color());
+                self.draw_placeholder(ui, self.get_color(), size);
             }
         });
 
-        signal
+        signals
+    }
+
+    pub fn draw_mini(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_centered(|ui| {
+            let size = ui.available_height().min(ui.available_width()).max(16.0);
+            let Some(clock) = self.get_clock() else {
+                circular_progress_bar::draw_thin(ui, size, self.get_color(), 0.0);
+                ui.label("No Timers Set");
+                return;
+            };
+
+            circular_progress_bar::draw_thin(ui, size, self.get_color(), clock.fraction());
+            ui.vertical(|ui| {
+                if self.ready {
+                    ui.strong(READY_TEXT);
+                } else if let Some(interval) = self.timers.get()
+                    && !interval.label.is_empty()
+                {
+                    ui.label(&interval.label);
+                }
+
+                let text_size = (size * 0.3).max(12.0);
+                ui.label(
+                    egui::RichText::new(clock.to_string())
+                        .size(text_size)
+                        .monospace(),
+                );
+            });
+        });
+    }
+
+    // Fills the available space and draws nothing clickable,
+    // the timer is driven by keyboard shortcuts only
+    pub fn draw_presentation(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            let available = ui.available_size();
+            let text_size = (available.y * 0.06).max(18.0);
+            let label = self
+                .timers
+                .get()
+                .map(|interval| interval.label.clone())
+                .unwrap_or_default();
+
+            ui.label(egui::RichText::new(label).size(text_size).strong());
+
+            let size = available
+                .x
+                .min(available.y - text_size * 3.0)
+                .max(MIN_RING_SIZE);
+            match self.get_clock() {
+                Some(clock) => {
+                    self.draw_progress(ui, clock, self.get_color(), size);
+                }
+                None => self.draw_placeholder(ui, self.get_color(), size),
+            }
+
+            match self.ready {
+                true => ui.label(egui::RichText::new(READY_TEXT).size(text_size).strong()),
+                false => ui.label(
+                    egui::RichText::new(self.next_up())
+                        .size(text_size * 0.7)
+                        .weak(),
+                ),
+            };
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::sync::OnceLock;
+
+    use super::*;
+
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+    thread_local! {
+        // Each test runs on its own thread, with its own time
+        static OFFSET: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    }
+
+    fn simulated_now() -> Instant {
+        *EPOCH.get_or_init(Instant::now) + OFFSET.with(Cell::get)
+    }
+
+    fn wait(millis: u64) {
+        OFFSET.with(|offset| offset.set(offset.get() + Duration::from_millis(millis)));
+    }
+
+    fn element(millis: &[u64]) -> CountdownElement {
+        let timers: Vec<Timer> = millis.iter().map(|&ms| Timer::new(ms, (0, 0, 0))).collect();
+        CountdownElement::new_with(&timers, false, CatchUp::PlayOneChime, simulated_now)
+    }
+
+    fn finished(signals: &[CountdownSignal]) -> usize {
+        signals
+            .iter()
+            .filter(|signal| matches!(signal, CountdownSignal::Finished(_)))
+            .count()
+    }
+
+    #[test]
+    fn starts_each_interval_when_the_last_one_was_due() {
+        let mut countdown = element(&[1000, 1000, 1000]);
+
+        wait(1050);
+        assert_eq!(finished(&countdown.tick()), 1);
+        assert_eq!(countdown.index(), 1);
+        assert_eq!(countdown.elapsed(), Duration::from_millis(50));
+
+        wait(900);
+        assert!(countdown.tick().is_empty());
+        wait(80);
+        assert_eq!(finished(&countdown.tick()), 1);
+
+        // 2030 ms in, two whole intervals and 30 ms of the third
+        assert_eq!(countdown.index(), 2);
+        assert_eq!(countdown.elapsed(), Duration::from_millis(30));
+    }
+
+    #[test]
+    fn reports_how_late_transitions_were_noticed() {
+        let mut countdown = element(&[500, 500, 500]);
+        // Noticed at 510, 1070 and 1570 ms
+        for millis in [510, 560, 500] {
+            wait(millis);
+            countdown.tick();
+        }
+
+        let report = countdown.drift_report();
+        assert_eq!(report.transitions, 3);
+        assert_eq!(report.max_latency, Duration::from_millis(70));
+        // Lateness never adds up, each interval starts on schedule
+        assert_eq!(report.total_latency, Duration::from_millis(10 + 70 + 70));
+    }
+
+    #[test]
+    fn previous_walks_back_through_the_played_rounds() {
+        let mut countdown = element(&[1000, 2000, 3000, 4000]);
+        countdown.set_shuffle(ShuffleSettings {
+            shuffle: true,
+            seed: Some(3),
+            ..ShuffleSettings::default()
+        });
+
+        let mut played = vec![];
+        for _ in 0..3 {
+            played.push(countdown.intervals());
+            for _ in 0..4 {
+                countdown.next();
+            }
+        }
+        let current = countdown.intervals();
+
+        // Back through each earlier round, newest first
+        for round in played.iter().rev() {
+            for _ in 0..4 {
+                countdown.prev();
+            }
+            assert_eq!(&countdown.intervals(), round);
+            assert_eq!(countdown.index(), 0);
+        }
+        assert_eq!(countdown.round(), 0);
+
+        // and forward again through the same rounds, not new ones
+        for round in played.iter().skip(1).chain([&current]) {
+            for _ in 0..4 {
+                countdown.next();
+            }
+            assert_eq!(&countdown.intervals(), round);
+        }
+    }
+
+    #[test]
+    fn forgets_the_oldest_played_rounds() {
+        let mut countdown = element(&[1000, 2000]);
+        countdown.set_shuffle(ShuffleSettings {
+            shuffle: true,
+            ..ShuffleSettings::default()
+        });
+        for _ in 0..(MAX_PLAYED_ROUNDS + 20) * 2 {
+            countdown.next();
+        }
+        assert_eq!(countdown.played.len(), MAX_PLAYED_ROUNDS);
+    }
+
+    #[test]
+    fn holds_intervals_however_they_are_reached() {
+        let mut countdown = element(&[1000, 1000, 1000]);
+        let mut timers = countdown.intervals();
+        timers[1].advance = Advance::Hold;
+        countdown.set_round(&timers, 0);
+
+        countdown.jump_to(1);
+        assert!(countdown.is_ready() && !countdown.is_running());
+
+        countdown.toggle_running();
+        countdown.jump_to(2);
+        assert!(countdown.is_running());
+        countdown.prev();
+        assert!(countdown.is_ready() && !countdown.is_running());
+
+        countdown.prev();
+        countdown.next();
+        assert!(countdown.is_ready() && !countdown.is_running());
+    }
+
+    #[test]
+    fn stops_catching_up_when_a_round_takes_no_time() {
+        let timers = [Timer::new(0, (0, 0, 0)), Timer::new(0, (0, 0, 0))];
+        let mut countdown =
+            CountdownElement::new_with(&timers, false, CatchUp::SkipMissedChimes, simulated_now);
+
+        wait(5000);
+        let signals = countdown.tick();
+        assert!(!signals.is_empty());
+        assert!(signals.len() <= timers.len() + 1);
+    }
+
+    #[test]
+    fn catches_up_on_every_interval_missed() {
+        let mut countdown = element(&[0, 1000]);
+        countdown.catch_up = CatchUp::SkipMissedChimes;
+
+        wait(3500);
+        // 0 and 1000 three times over, then halfway through the last 1000
+        assert_eq!(countdown.tick().len(), 7);
+        assert_eq!(countdown.index(), 1);
+        assert_eq!(countdown.elapsed(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn pauses_on_resume_even_when_nothing_finished() {
+        let mut countdown = element(&[10_000]);
+        countdown.catch_up = CatchUp::PauseOnResume;
+
+        wait(1000);
+        countdown.tick();
+        wait(5000);
+        assert!(countdown.tick().is_empty());
+        assert!(countdown.is_paused());
+        assert_eq!(countdown.elapsed(), Duration::from_millis(1000));
+
+        // Short frames leave a paused timer alone
+        wait(100);
+        countdown.tick();
+        wait(5000);
+        countdown.tick();
+        assert_eq!(countdown.elapsed(), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn only_next_finishes_open_ended_intervals() {
+        let mut open = Timer::new(0, (0, 0, 0));
+        open.kind = TimerKind::OpenEnded;
+        let timers = [open, Timer::new(1000, (0, 0, 0))];
+        let mut countdown =
+            CountdownElement::new_with(&timers, false, CatchUp::PlayOneChime, simulated_now);
+
+        let skipped = |signal| matches!(signal, Some(CountdownSignal::Skipped(_)));
+
+        // Jumping or stepping back only leaves the open-ended interval
+        wait(100);
+        assert!(skipped(countdown.jump_to(1)));
+        countdown.jump_to(0);
+        wait(100);
+        assert!(skipped(countdown.previous()));
+
+        // Next ends it
+        countdown.jump_to(0);
+        countdown.toggle_running();
+        wait(100);
+        assert!(matches!(
+            countdown.skip(),
+            Some(CountdownSignal::Finished(_))
+        ));
+    }
+
+    #[test]
+    fn reports_when_a_round_is_completed() {
+        let mut countdown = element(&[1000, 1000]);
+        let completed = |signals: Vec<CountdownSignal>| {
+            signals
+                .into_iter()
+                .map(|signal| match signal {
+                    CountdownSignal::Finished(summary) => summary.completed_round,
+                    _ => false,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        wait(1000);
+        assert_eq!(completed(countdown.tick()), [false]);
+        wait(1000);
+        assert_eq!(completed(countdown.tick()), [true]);
+
+        // Going back to the first interval is not finishing the round
+        countdown.jump_to(1);
+        wait(100);
+        assert!(
+            countdown
+                .jump_to(0)
+                .is_some_and(|signal| completed(vec![signal]) == [false])
+        );
+    }
+
+    #[test]
+    fn transitions_follow_the_injected_clock() {
+        let mut countdown = element(&[1000, 1000]);
+        countdown.set_animation(Some(Duration::from_millis(300)));
+
+        wait(1000);
+        countdown.tick();
+        let started = countdown.transition.as_ref().map(|v| v.started);
+        assert_eq!(started, Some(simulated_now()));
+    }
+
+    #[test]
+    fn paused_time_does_not_count() {
+        let mut countdown = element(&[1000, 1000]);
+        wait(400);
+        countdown.toggle_running();
+        wait(5000);
+        assert!(countdown.tick().is_empty());
+        countdown.toggle_running();
+
+        wait(599);
+        assert!(countdown.tick().is_empty());
+        wait(1);
+        assert_eq!(finished(&countdown.tick()), 1);
+        assert_eq!(countdown.elapsed(), Duration::ZERO);
     }
 }
@@ -0,0 +1,88 @@
+use eframe::egui;
+use egui::Color32;
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+use crate::internal::settings_parser::EffectSettings;
+
+// Strongest tint the overlay reaches at full intensity
+const MAX_ALPHA: f32 = 0.6;
+const BORDER_WIDTH: f32 = 12.0;
+
+pub struct Effects {
+    position: Option<(usize, u32)>,
+    changed_at: Option<Instant>,
+    color: Color32,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Self {
+            position: None,
+            changed_at: None,
+            color: Color32::TRANSPARENT,
+        }
+    }
+
+    // Paints over the whole window, after everything else has been drawn
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &EffectSettings,
+        position: (usize, u32),
+        color: Color32,
+        remaining: Option<Duration>,
+    ) {
+        if self.position.is_some_and(|v| v != position) {
+            self.changed_at = Some(Instant::now());
+            self.color = color;
+        }
+        self.position = Some(position);
+
+        let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("effects"));
+        let painter = ctx.layer_painter(layer);
+        let rect = ctx.screen_rect();
+        let duration = Duration::from_millis(settings.duration_ms);
+
+        if settings.flash
+            && let Some(changed_at) = self.changed_at
+            && changed_at.elapsed() < duration
+        {
+            let t = changed_at.elapsed().div_duration_f32(duration);
+
+            // Without motion the cue is a steady frame that disappears at once
+            if settings.reduce_motion {
+                let stroke = egui::Stroke::new(BORDER_WIDTH, self.color);
+                painter.rect_stroke(rect.shrink(BORDER_WIDTH / 2.0), 0.0, stroke);
+            } else {
+                let alpha = settings.intensity * MAX_ALPHA * (1.0 - ease_out(t));
+                painter.rect_filled(rect, 0.0, self.color.gamma_multiply(alpha));
+            }
+            ctx.request_repaint();
+        }
+
+        let in_final_seconds = remaining
+            .is_some_and(|v| settings.pulse_seconds > 0 && v.as_secs() < settings.pulse_seconds);
+        if in_final_seconds && !settings.reduce_motion {
+            // One beat per second, peaking as each second ticks over
+            let remaining = remaining.unwrap_or_default().as_secs_f32();
+            let beat = 0.5 + 0.5 * (remaining * TAU).cos();
+            let alpha = settings.intensity * MAX_ALPHA * 0.5 * beat;
+            painter.rect_filled(rect, 0.0, color.gamma_multiply(alpha));
+        } else if in_final_seconds {
+            let stroke = egui::Stroke::new(BORDER_WIDTH / 2.0, color);
+            painter.rect_stroke(rect.shrink(BORDER_WIDTH / 4.0), 0.0, stroke);
+        }
+    }
+}
+
+pub fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(2)
+}
+
+pub fn lerp_color(from: Color32, to: Color32, t: f32) -> Color32 {
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    let [r1, g1, b1, a1] = from.to_array();
+    let [r2, g2, b2, a2] = to.to_array();
+    Color32::from_rgba_premultiplied(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2), lerp(a1, a2))
+}
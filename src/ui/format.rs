@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+// Whole seconds as m:ss, or h:mm:ss from an hour up
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs >= 3600 {
+        true => format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60),
+        false => format!("{}:{:02}", secs / 60, secs % 60),
+    }
+}
+
+// Time added up over many intervals, e.g. 1h 05m or 12m 30s, which
+// reads as an amount rather than as a clock
+pub fn format_total(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let hours = secs / 3600;
+    let minutes = (secs / 60) % 60;
+    let seconds = secs % 60;
+
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
+}
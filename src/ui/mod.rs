@@ -1,4 +1,15 @@
 mod circular_progress_bar;
 
 pub mod countdown;
+pub mod effects;
+pub mod format;
+pub mod palette;
+pub mod progress;
+pub mod session;
 pub mod settings;
+pub mod shortcuts;
+pub mod statistics;
+pub mod templates;
+pub mod theme;
+pub mod webhooks;
+pub mod window_title;
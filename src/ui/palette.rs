@@ -0,0 +1,147 @@
+use eframe::egui;
+
+use crate::internal::fuzzy::fuzzy_score;
+use crate::internal::keybindings::{Action, KeyBindings};
+
+const MAX_RESULTS: usize = 10;
+
+pub enum PaletteSignal {
+    Run(Action),
+    LoadProfile(String),
+    Close,
+}
+
+#[derive(Clone)]
+enum Entry {
+    Action(Action),
+    Profile(String),
+}
+
+pub struct CommandPalette {
+    query: String,
+    selected: usize,
+}
+
+impl Entry {
+    fn title(&self) -> String {
+        match self {
+            Entry::Action(action) => action.label().to_string(),
+            Entry::Profile(name) => format!("Load profile: {}", name),
+        }
+    }
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    fn matches(&self, profiles: &[String]) -> Vec<Entry> {
+        let entries = Action::ALL
+            .into_iter()
+            .filter(|action| *action != Action::CommandPalette)
+            .map(Entry::Action)
+            .chain(profiles.iter().cloned().map(Entry::Profile));
+
+        let mut scored: Vec<(i32, Entry)> = entries
+            .filter_map(|entry| Some((fuzzy_score(&self.query, &entry.title())?, entry)))
+            .collect();
+        // Stable sort keeps the natural order between equal scores
+        scored.sort_by_key(|(score, _)| -score);
+
+        scored
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        bindings: &KeyBindings,
+        profiles: &[String],
+    ) -> Option<PaletteSignal> {
+        let mut update = None;
+        let matches = self.matches(profiles);
+
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 48.0])
+            .show(ctx, |ui| {
+                ui.set_width(360.0);
+
+                let input = egui::TextEdit::singleline(&mut self.query)
+                    .hint_text("Type a command or profile")
+                    .desired_width(f32::INFINITY);
+                let response = ui.add(input);
+                response.request_focus();
+                if response.changed() {
+                    self.selected = 0;
+                }
+
+                ui.separator();
+                if matches.is_empty() {
+                    ui.label("No matches");
+                }
+
+                for (i, entry) in matches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let selectable = ui.selectable_label(i == self.selected, entry.title());
+                        if selectable.clicked() {
+                            update = Some(select(entry));
+                        }
+
+                        if let Entry::Action(action) = entry
+                            && let Some(binding) = bindings.get(*action)
+                        {
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    ui.weak(binding.to_string());
+                                },
+                            );
+                        }
+                    });
+                }
+            });
+
+        if enter && let Some(entry) = matches.get(self.selected) {
+            update = Some(select(entry));
+        }
+
+        if escape {
+            update = Some(PaletteSignal::Close);
+        }
+
+        update
+    }
+}
+
+fn select(entry: &Entry) -> PaletteSignal {
+    match entry {
+        Entry::Action(action) => PaletteSignal::Run(*action),
+        Entry::Profile(name) => PaletteSignal::LoadProfile(name.clone()),
+    }
+}
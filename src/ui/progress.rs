@@ -0,0 +1,110 @@
+use eframe::egui;
+use egui::{Color32, Rect, Stroke, Vec2};
+
+use crate::internal::settings_parser::ProgressStyle;
+use crate::ui::{circular_progress_bar, theme};
+
+pub struct Progress<'a> {
+    pub color: Color32,
+    pub frac: f32,
+    pub text: &'a str,
+    pub ring_thickness: f32,
+    // Relative length and color of every interval in the session
+    pub segments: &'a [(f32, Color32)],
+    pub current: usize,
+}
+
+pub fn draw(ui: &mut egui::Ui, style: ProgressStyle, size: f32, progress: &Progress) {
+    let Progress {
+        color, frac, text, ..
+    } = *progress;
+
+    match style {
+        ProgressStyle::Pie => {
+            circular_progress_bar::draw(ui, size, size, color, frac, Some(text));
+        }
+        ProgressStyle::Ring => {
+            let thickness = progress.ring_thickness;
+            circular_progress_bar::draw_ring(ui, size, color, frac, thickness, Some(text));
+        }
+        ProgressStyle::Session => {
+            let segments = progress.segments;
+            let current = progress.current;
+            circular_progress_bar::draw_session(ui, size, segments, current, frac, Some(text));
+        }
+        ProgressStyle::Bar => draw_bar(ui, size, color, frac, text),
+        ProgressStyle::Digital => draw_digital(ui, size, color, text),
+        ProgressStyle::Hourglass => draw_hourglass(ui, size, color, frac, text),
+    }
+}
+
+fn draw_bar(ui: &mut egui::Ui, size: f32, color: Color32, frac: f32, text: &str) {
+    let text_size = (size * 0.1 * theme::timer_font_scale(ui.style())).max(14.0);
+    ui.add_space(size * 0.3);
+    ui.label(egui::RichText::new(text).size(text_size));
+
+    let desired_size = Vec2::new(size, (size * 0.08).max(8.0));
+    let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::hover());
+    let rect = response.rect;
+    let rounding = rect.height() / 2.0;
+
+    painter.rect_filled(rect, rounding, color.linear_multiply(0.05));
+    let filled = Rect::from_min_size(rect.min, Vec2::new(rect.width() * frac, rect.height()));
+    painter.rect_filled(filled, rounding, color);
+    ui.add_space(size * 0.3);
+}
+
+fn draw_digital(ui: &mut egui::Ui, size: f32, color: Color32, text: &str) {
+    let text_size = (size * 0.2 * theme::timer_font_scale(ui.style())).max(16.0);
+    let color = theme::readable_on_panel(ui.style(), color);
+    ui.add_space(size * 0.35);
+    ui.label(
+        egui::RichText::new(text)
+            .size(text_size)
+            .monospace()
+            .color(color),
+    );
+    ui.add_space(size * 0.35);
+}
+
+// Sand drains from the top chamber into the bottom one
+fn draw_hourglass(ui: &mut egui::Ui, size: f32, color: Color32, frac: f32, text: &str) {
+    let text_size = (size * 0.08 * theme::timer_font_scale(ui.style())).max(14.0);
+    let desired_size = Vec2::new(size * 0.4, size - text_size * 2.0);
+    let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::hover());
+
+    let rect = response.rect;
+    let neck = rect.height() * 0.04;
+    let chamber = (rect.height() - neck) / 2.0;
+    let top = Rect::from_min_size(rect.min, Vec2::new(rect.width(), chamber));
+    let bottom = Rect::from_min_size(
+        egui::pos2(rect.min.x, rect.max.y - chamber),
+        Vec2::new(rect.width(), chamber),
+    );
+
+    let outline = Stroke::new(2.0, color.linear_multiply(0.3));
+    let rounding = rect.width() * 0.1;
+    for half in [top, bottom] {
+        painter.rect_filled(half, rounding, color.linear_multiply(0.05));
+        painter.rect_stroke(half, rounding, outline);
+    }
+
+    let remaining = chamber * (1.0 - frac);
+    let sand_top = Rect::from_min_max(egui::pos2(top.min.x, top.max.y - remaining), top.max);
+    painter.rect_filled(sand_top, rounding, color);
+
+    let fallen = chamber * frac;
+    let sand_bottom =
+        Rect::from_min_max(egui::pos2(bottom.min.x, bottom.max.y - fallen), bottom.max);
+    painter.rect_filled(sand_bottom, rounding, color);
+
+    if frac > 0.0 && frac < 1.0 {
+        let stream = [
+            egui::pos2(rect.center().x, top.max.y),
+            egui::pos2(rect.center().x, bottom.max.y - fallen),
+        ];
+        painter.line_segment(stream, Stroke::new(2.0, color));
+    }
+
+    ui.label(egui::RichText::new(text).size(text_size));
+}
@@ -0,0 +1,94 @@
+use chrono::Local;
+use eframe::egui;
+
+use crate::internal::timer::TimerKind;
+use crate::ui::countdown::SessionOverview;
+use crate::ui::format::format_duration;
+
+pub enum SessionSignal {
+    JumpTo(usize),
+}
+
+pub struct SessionPanel {
+    scrolled_to: Option<usize>,
+}
+
+impl SessionPanel {
+    pub fn new() -> Self {
+        Self { scrolled_to: None }
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui, overview: &SessionOverview) -> Option<SessionSignal> {
+        let mut update = None;
+
+        ui.heading("Session");
+        ui.label(format!("Round {}", overview.round + 1));
+        ui.label(format!("Elapsed: {}", format_duration(overview.elapsed)));
+
+        let plus = if overview.open_ended { "+" } else { "" };
+        ui.label(format!(
+            "Remaining: {}{}",
+            format_duration(overview.remaining),
+            plus
+        ));
+
+        let total = overview.elapsed + overview.remaining;
+        let progress = match total.is_zero() {
+            true => 0.0,
+            false => overview.elapsed.div_duration_f32(total),
+        };
+        ui.add(egui::ProgressBar::new(progress).show_percentage());
+
+        let finish = Local::now() + overview.remaining;
+        let finish = match overview.running {
+            true => format!("Finishes at {}{}", finish.format("%H:%M"), plus),
+            false => format!(
+                "Finishes at {}{} if resumed now",
+                finish.format("%H:%M"),
+                plus
+            ),
+        };
+        ui.label(finish);
+
+        ui.separator();
+
+        // Only follow the current interval when it changes,
+        // so the list can still be scrolled by hand
+        let follow = self.scrolled_to != Some(overview.current);
+        self.scrolled_to = Some(overview.current);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (i, interval) in overview.intervals.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let (swatch, _) =
+                        ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                    ui.painter().rect_filled(swatch, 2.0, interval.color);
+
+                    let name = match interval.label.is_empty() {
+                        true => format!("Interval {}", i + 1),
+                        false => interval.label.clone(),
+                    };
+                    let length = match interval.kind {
+                        TimerKind::Countdown => format_duration(interval.duration),
+                        kind => kind.label().to_string(),
+                    };
+
+                    let mut text = egui::RichText::new(format!("{}  {}", name, length));
+                    if i < overview.current {
+                        text = text.weak();
+                    }
+
+                    let response = ui.selectable_label(i == overview.current, text);
+                    if follow && i == overview.current {
+                        response.scroll_to_me(Some(egui::Align::Center));
+                    }
+                    if response.clicked() && i != overview.current {
+                        update = Some(SessionSignal::JumpTo(i));
+                    }
+                });
+            }
+        });
+
+        update
+    }
+}
@@ -1,30 +1,82 @@
 use eframe::egui;
 
+use crate::internal::settings_parser::{
+    AppSettings, CatchUp, EffectSettings, MqttSettings, ProgressStyle, ShuffleSettings,
+};
+use crate::internal::theme::{Palette, Theme};
+use crate::internal::timer::{Advance, Timer, TimerKind, sanitize_label};
+use crate::internal::title::DEFAULT_TITLE_FORMAT;
+
 pub enum SettingsSignal {
-    UpdateTimer((usize, (u64, (u8, u8, u8)))),
+    UpdateTimer((usize, Timer)),
     AddTimer,
     UpdatePlayOnce(bool),
+    UpdateKeepAdjustments(bool),
+    UpdateCatchUp(CatchUp),
+    UpdateShuffle(ShuffleSettings),
+    ApplyShuffle,
+    UpdateProgressStyle(ProgressStyle),
+    UpdateRingThickness(f32),
+    UpdateEffects(EffectSettings),
+    UpdateTheme(Theme),
+    UpdateFontSize(f32),
+    UpdateTimerFontScale(f32),
+    UpdateTitleFormat(String),
+    ApplyPalette(Palette),
+    UpdatePaletteName(String),
+    SavePalette,
+    DeletePalette(usize),
+    UpdateNotifications(bool),
+    UpdateNotifyBeforeSeconds(u64),
+    UpdateMpris(bool),
+    UpdateOsc(bool),
+    UpdateOscTarget(String),
+    UpdateOscBind(String),
+    UpdateOscListenPort(u16),
+    RestartOsc,
+    UpdateMqtt(MqttSettings),
+    RestartMqtt,
+    UpdateScript(String),
+    LoadScript,
     UpdateVolume(f32),
     UpdateUseCustomSound(bool),
     UpdateCustomSoundLocation(String),
+    UpdateSequenceText(String),
+    ApplySequence,
     SaveSettings,
 }
 
+// Problems found outside of this view, shown next to what caused them
+pub struct SettingsErrors<'a> {
+    pub sequence: Option<&'a str>,
+    pub notifications: Option<&'a str>,
+    pub mpris: Option<&'a str>,
+    pub osc: Option<&'a str>,
+    pub mqtt: Option<&'a str>,
+    pub script: Option<&'a str>,
+}
+
 pub fn draw(
     ui: &mut egui::Ui,
-    timers: &Vec<(u64, (u8, u8, u8))>,
-    play_once: bool,
-    volume: f32,
-    use_custom_sound: bool,
-    custom_sound_location: &String,
+    settings: &AppSettings,
+    sequence_text: &str,
+    palette_name: &str,
+    script_output: &[String],
+    errors: &SettingsErrors,
 ) -> Option<SettingsSignal> {
     let mut update = None;
+    let timers = &settings.timers;
+    let play_once = settings.play_once;
+    let catch_up = settings.catch_up;
+    let volume = settings.volume;
+    let use_custom_sound = settings.use_custom_sound;
+    let custom_sound_location = &settings.custom_sound_location;
 
     ui.vertical_centered_justified(|ui| {
         ui.group(|ui| {
             ui.heading("Timers");
             for (i, timer) in timers.iter().enumerate() {
-                if let Some(change) = draw_edit_timer(ui, *timer) {
+                if let Some(change) = draw_edit_timer(ui, i, timer) {
                     update = Some(SettingsSignal::UpdateTimer((i, change)));
                 }
             }
@@ -38,9 +90,177 @@ pub fn draw(
                 if ui.toggle_value(&mut play_once, "Play once?").changed() {
                     update = Some(SettingsSignal::UpdatePlayOnce(play_once))
                 }
+
+                let mut keep_adjustments = settings.keep_adjustments;
+                if ui
+                    .checkbox(&mut keep_adjustments, "Keep time added or taken off")
+                    .on_hover_text(
+                        "Writes quick adjustments back to the timers and the loaded profile, \
+                         instead of only the current run. Not available while shuffling \
+                         or with a sequence loaded",
+                    )
+                    .changed()
+                {
+                    update = Some(SettingsSignal::UpdateKeepAdjustments(keep_adjustments));
+                }
+
+                let mut catch_up = catch_up;
+                egui::ComboBox::from_label("After missed intervals")
+                    .selected_text(catch_up.label())
+                    .show_ui(ui, |ui| {
+                        for option in CatchUp::ALL {
+                            if ui
+                                .selectable_value(&mut catch_up, option, option.label())
+                                .changed()
+                            {
+                                update = Some(SettingsSignal::UpdateCatchUp(catch_up));
+                            }
+                        }
+                    });
             });
         });
 
+        ui.group(|ui| {
+            ui.heading("Shuffle");
+            if let Some(shuffle) = draw_shuffle(ui, timers, &settings.shuffle) {
+                update = Some(SettingsSignal::UpdateShuffle(shuffle));
+            }
+            if ui
+                .button("Apply")
+                .on_hover_text("Deals a new round and starts the sequence over")
+                .clicked()
+            {
+                update = Some(SettingsSignal::ApplyShuffle);
+            }
+        });
+
+        ui.group(|ui| {
+            ui.heading("Sequence");
+            let mut text = sequence_text.to_string();
+            let input = egui::TextEdit::multiline(&mut text)
+                .desired_rows(3)
+                .code_editor()
+                .hint_text("warmup 5m blue; 8x(work 20s red, rest 10s green); cooldown 3m #8888ff");
+            if ui.add(input).changed() {
+                update = Some(SettingsSignal::UpdateSequenceText(text));
+            }
+
+            if let Some(error) = errors.sequence {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            if ui.button("Apply sequence").clicked() {
+                update = Some(SettingsSignal::ApplySequence);
+            }
+        });
+
+        ui.group(|ui| {
+            ui.heading("Script");
+            if let Some(signal) = draw_script(ui, &settings.script, script_output, errors.script) {
+                update = Some(signal);
+            }
+        });
+
+        ui.group(|ui| {
+            ui.heading("Display");
+            let mut progress_style = settings.progress_style;
+            egui::ComboBox::from_label("Progress style")
+                .selected_text(progress_style.label())
+                .show_ui(ui, |ui| {
+                    for option in ProgressStyle::ALL {
+                        if ui
+                            .selectable_value(&mut progress_style, option, option.label())
+                            .changed()
+                        {
+                            update = Some(SettingsSignal::UpdateProgressStyle(progress_style));
+                        }
+                    }
+                });
+
+            if progress_style == ProgressStyle::Ring {
+                let mut ring_thickness = settings.ring_thickness;
+                let slider =
+                    egui::Slider::new(&mut ring_thickness, 0.05..=1.0).text("Ring thickness");
+                if ui.add(slider).changed() {
+                    update = Some(SettingsSignal::UpdateRingThickness(ring_thickness));
+                }
+            }
+        });
+
+        ui.group(|ui| {
+            ui.heading("Appearance");
+            if let Some(signal) = draw_appearance(ui, settings, palette_name) {
+                update = Some(signal);
+            }
+        });
+
+        ui.group(|ui| {
+            ui.heading("Effects");
+            if let Some(effects) = draw_effects(ui, &settings.effects) {
+                update = Some(SettingsSignal::UpdateEffects(effects));
+            }
+        });
+
+        ui.group(|ui| {
+            ui.heading("Desktop");
+            let mut notifications = settings.notifications;
+            if ui
+                .checkbox(
+                    &mut notifications,
+                    "Desktop notifications while the window is in the background",
+                )
+                .changed()
+            {
+                update = Some(SettingsSignal::UpdateNotifications(notifications));
+            }
+
+            if let Some(error) = errors.notifications {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            let mut notify_before_seconds = settings.notify_before_seconds;
+            let input = egui::DragValue::new(&mut notify_before_seconds)
+                .clamp_range(0..=600)
+                .prefix("Announce the next interval ")
+                .suffix(" s ahead");
+            if ui
+                .add_enabled(notifications, input)
+                .on_hover_text("0 turns the announcement off")
+                .changed()
+            {
+                update = Some(SettingsSignal::UpdateNotifyBeforeSeconds(
+                    notify_before_seconds,
+                ));
+            }
+
+            let mut mpris = settings.mpris;
+            if ui
+                .checkbox(&mut mpris, "Control with media keys")
+                .on_hover_text("Shows the timer as a media player over MPRIS")
+                .changed()
+            {
+                update = Some(SettingsSignal::UpdateMpris(mpris));
+            }
+
+            if let Some(error) = errors.mpris {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+
+        ui.group(|ui| {
+            ui.heading("OSC");
+            if let Some(signal) = draw_osc(ui, settings, errors.osc) {
+                update = Some(signal);
+            }
+        });
+
+        ui.group(|ui| {
+            ui.heading("MQTT");
+            if let Some(signal) = draw_mqtt(ui, &settings.mqtt, errors.mqtt) {
+                update = Some(signal);
+            }
+        });
+
         ui.group(|ui| {
             ui.heading("Audio");
             let mut volume = volume;
@@ -85,9 +305,437 @@ pub fn draw(
     update
 }
 
-fn draw_edit_timer(ui: &mut egui::Ui, timer: (u64, (u8, u8, u8))) -> Option<(u64, (u8, u8, u8))> {
-    let mut color = timer.1.into();
-    let (mut hrs, mut mins, mut secs, mut ms) = millis_to_time(timer.0);
+fn draw_appearance(
+    ui: &mut egui::Ui,
+    settings: &AppSettings,
+    palette_name: &str,
+) -> Option<SettingsSignal> {
+    let mut update = None;
+
+    let mut theme = settings.theme;
+    egui::ComboBox::from_label("Theme")
+        .selected_text(theme.label())
+        .show_ui(ui, |ui| {
+            for option in Theme::ALL {
+                if ui
+                    .selectable_value(&mut theme, option, option.label())
+                    .changed()
+                {
+                    update = Some(SettingsSignal::UpdateTheme(theme));
+                }
+            }
+        });
+
+    let mut font_size = settings.font_size;
+    let slider = egui::Slider::new(&mut font_size, 8.0..=32.0).text("Font size");
+    if ui.add(slider).changed() {
+        update = Some(SettingsSignal::UpdateFontSize(font_size));
+    }
+
+    let mut timer_font_scale = settings.timer_font_scale;
+    let slider = egui::Slider::new(&mut timer_font_scale, 0.5..=3.0).text("Timer text scale");
+    if ui.add(slider).changed() {
+        update = Some(SettingsSignal::UpdateTimerFontScale(timer_font_scale));
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Window title");
+        let mut title_format = settings.title_format.clone();
+        let input = egui::TextEdit::singleline(&mut title_format).hint_text(DEFAULT_TITLE_FORMAT);
+        if ui
+            .add(input)
+            .on_hover_text(
+                "{time}, {remaining}, {elapsed}, {label}, {index}, {count}, {round}, {percent}, {state}",
+            )
+            .changed()
+        {
+            update = Some(SettingsSignal::UpdateTitleFormat(title_format));
+        }
+    });
+
+    ui.label("Palettes");
+    let built_in = Palette::built_in();
+    let user = settings
+        .palettes
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (Some(i), v));
+    for (user_index, palette) in built_in.iter().map(|v| (None, v)).chain(user) {
+        ui.horizontal(|ui| {
+            for (r, g, b) in &palette.colors {
+                let (swatch, _) =
+                    ui.allocate_exact_size(egui::vec2(14.0, 14.0), egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(swatch, 2.0, egui::Color32::from_rgb(*r, *g, *b));
+            }
+            ui.label(&palette.name);
+
+            if ui
+                .button("Apply")
+                .on_hover_text("Recolor the timers with this palette")
+                .clicked()
+            {
+                update = Some(SettingsSignal::ApplyPalette(palette.clone()));
+            }
+            if let Some(i) = user_index
+                && ui.button("Delete").clicked()
+            {
+                update = Some(SettingsSignal::DeletePalette(i));
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        let mut name = palette_name.to_string();
+        let input = egui::TextEdit::singleline(&mut name)
+            .desired_width(140.0)
+            .hint_text("Palette name");
+        if ui.add(input).changed() {
+            update = Some(SettingsSignal::UpdatePaletteName(name));
+        }
+
+        let can_save = !palette_name.trim().is_empty();
+        if ui
+            .add_enabled(can_save, egui::Button::new("Save timer colors"))
+            .clicked()
+        {
+            update = Some(SettingsSignal::SavePalette);
+        }
+    });
+
+    update
+}
+
+fn draw_shuffle(
+    ui: &mut egui::Ui,
+    pool: &[Timer],
+    shuffle: &ShuffleSettings,
+) -> Option<ShuffleSettings> {
+    let mut shuffle = *shuffle;
+    let mut changed = false;
+
+    changed |= ui
+        .checkbox(&mut shuffle.shuffle, "Shuffle the order every round")
+        .changed();
+    changed |= ui
+        .add(
+            egui::DragValue::new(&mut shuffle.pick)
+                .clamp_range(0..=pool.len())
+                .prefix("Pick ")
+                .suffix(format!(" of {} each round", pool.len())),
+        )
+        .on_hover_text("0 plays every interval")
+        .changed();
+
+    let mut random_durations = shuffle.max_secs > 0;
+    if ui
+        .checkbox(&mut random_durations, "Random countdown durations")
+        .changed()
+    {
+        shuffle.max_secs = match random_durations {
+            true => shuffle.min_secs.max(1) * 2,
+            false => 0,
+        };
+        changed = true;
+    }
+    if random_durations {
+        ui.horizontal(|ui| {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut shuffle.min_secs)
+                        .clamp_range(0..=shuffle.max_secs)
+                        .prefix("From ")
+                        .suffix(" s"),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut shuffle.max_secs)
+                        .clamp_range(shuffle.min_secs.max(1)..=86_400)
+                        .prefix("to ")
+                        .suffix(" s"),
+                )
+                .changed();
+        });
+    }
+
+    ui.horizontal(|ui| {
+        let mut seeded = shuffle.seed.is_some();
+        if ui
+            .checkbox(&mut seeded, "Seed")
+            .on_hover_text("The same seed plays the same rounds every time")
+            .changed()
+        {
+            shuffle.seed = seeded.then_some(1);
+            changed = true;
+        }
+        if let Some(seed) = &mut shuffle.seed {
+            changed |= ui.add(egui::DragValue::new(seed)).changed();
+        }
+    });
+
+    if shuffle.is_active() {
+        ui.label("Pool");
+        ui.horizontal_wrapped(|ui| {
+            for (i, timer) in pool.iter().enumerate() {
+                let (r, g, b) = timer.color;
+                let name = match timer.label.is_empty() {
+                    true => format!("Interval {}", i + 1),
+                    false => timer.label.clone(),
+                };
+                ui.colored_label(egui::Color32::from_rgb(r, g, b), name);
+            }
+        });
+        ui.weak(describe_shuffle(pool.len(), &shuffle));
+    }
+
+    match changed {
+        true => Some(shuffle),
+        false => None,
+    }
+}
+
+fn describe_shuffle(pool: usize, shuffle: &ShuffleSettings) -> String {
+    let count = match shuffle.pick {
+        0 => pool,
+        pick => pick.min(pool),
+    };
+    let order = match shuffle.shuffle {
+        true => "in a random order",
+        false => "in their listed order",
+    };
+    let mut description = format!("Each round plays {} of {} intervals {}", count, pool, order);
+    if shuffle.max_secs > 0 {
+        let min = shuffle.min_secs.min(shuffle.max_secs);
+        description += &format!(", countdowns lasting {}-{} s", min, shuffle.max_secs);
+    }
+    description
+}
+
+fn draw_script(
+    ui: &mut egui::Ui,
+    path: &str,
+    output: &[String],
+    script_error: Option<&str>,
+) -> Option<SettingsSignal> {
+    let mut update = None;
+
+    ui.horizontal(|ui| {
+        ui.label("Script file");
+        let mut path = path.to_string();
+        let input = egui::TextEdit::singleline(&mut path)
+            .desired_width(220.0)
+            .hint_text("/path/to/workout.rhai");
+        if ui.add(input).changed() {
+            update = Some(SettingsSignal::UpdateScript(path));
+        }
+    });
+
+    let label = match path.is_empty() {
+        true => "Unload",
+        false => "Load",
+    };
+    if ui
+        .button(label)
+        .on_hover_text("Runs the script again, regenerating the sequence if it has sequence()")
+        .clicked()
+    {
+        update = Some(SettingsSignal::LoadScript);
+    }
+
+    if let Some(error) = script_error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    if !output.is_empty() {
+        egui::ScrollArea::vertical()
+            .id_source("script_output")
+            .max_height(80.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in output {
+                    ui.monospace(line);
+                }
+            });
+    }
+
+    update
+}
+
+fn draw_osc(
+    ui: &mut egui::Ui,
+    settings: &AppSettings,
+    osc_error: Option<&str>,
+) -> Option<SettingsSignal> {
+    let mut update = None;
+
+    let mut osc = settings.osc;
+    if ui
+        .checkbox(&mut osc, "Send and receive Open Sound Control")
+        .changed()
+    {
+        update = Some(SettingsSignal::UpdateOsc(osc));
+    }
+
+    ui.add_enabled_ui(osc, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Send to");
+            let mut target = settings.osc_target.clone();
+            let input = egui::TextEdit::singleline(&mut target)
+                .desired_width(160.0)
+                .hint_text("127.0.0.1:9000");
+            if ui.add(input).changed() {
+                update = Some(SettingsSignal::UpdateOscTarget(target));
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Listen on");
+            let mut bind = settings.osc_bind.clone();
+            let input = egui::TextEdit::singleline(&mut bind)
+                .desired_width(100.0)
+                .hint_text("127.0.0.1");
+            if ui
+                .add(input)
+                .on_hover_text("0.0.0.0 accepts commands from other machines")
+                .changed()
+            {
+                update = Some(SettingsSignal::UpdateOscBind(bind));
+            }
+
+            let mut listen_port = settings.osc_listen_port;
+            let input = egui::DragValue::new(&mut listen_port).prefix("port ");
+            if ui
+                .add(input)
+                .on_hover_text("0 turns listening off")
+                .changed()
+            {
+                update = Some(SettingsSignal::UpdateOscListenPort(listen_port));
+            }
+        });
+
+        if ui.button("Apply").clicked() {
+            update = Some(SettingsSignal::RestartOsc);
+        }
+    });
+
+    if let Some(error) = osc_error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    update
+}
+
+fn draw_mqtt(
+    ui: &mut egui::Ui,
+    mqtt: &MqttSettings,
+    mqtt_error: Option<&str>,
+) -> Option<SettingsSignal> {
+    let mut update = None;
+    let mut mqtt = mqtt.clone();
+
+    if ui
+        .checkbox(&mut mqtt.enabled, "Publish to an MQTT broker")
+        .changed()
+    {
+        return Some(SettingsSignal::UpdateMqtt(mqtt));
+    }
+
+    ui.add_enabled_ui(mqtt.enabled, |ui| {
+        let mut changed = false;
+        egui::Grid::new("mqtt").num_columns(2).show(ui, |ui| {
+            ui.label("Broker");
+            let input = egui::TextEdit::singleline(&mut mqtt.broker)
+                .desired_width(160.0)
+                .hint_text("127.0.0.1:1883");
+            changed |= ui.add(input).changed();
+            ui.end_row();
+
+            ui.label("Topic");
+            let input = egui::TextEdit::singleline(&mut mqtt.topic).desired_width(160.0);
+            changed |= ui.add(input).changed();
+            ui.end_row();
+
+            ui.label("User name");
+            let input = egui::TextEdit::singleline(&mut mqtt.username).desired_width(160.0);
+            changed |= ui.add(input).changed();
+            ui.end_row();
+
+            ui.label("Password");
+            let input = egui::TextEdit::singleline(&mut mqtt.password)
+                .desired_width(160.0)
+                .password(true);
+            changed |= ui.add(input).changed();
+            ui.end_row();
+        });
+        changed |= ui
+            .checkbox(&mut mqtt.discovery, "Home Assistant discovery")
+            .changed();
+
+        if changed {
+            update = Some(SettingsSignal::UpdateMqtt(mqtt.clone()));
+        }
+        if ui.button("Apply").clicked() {
+            update = Some(SettingsSignal::RestartMqtt);
+        }
+    });
+
+    if let Some(error) = mqtt_error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    update
+}
+
+fn draw_effects(ui: &mut egui::Ui, effects: &EffectSettings) -> Option<EffectSettings> {
+    let mut effects = *effects;
+    let mut changed = false;
+
+    changed |= ui
+        .checkbox(
+            &mut effects.flash,
+            "Flash the window when the interval changes",
+        )
+        .changed();
+    changed |= ui
+        .checkbox(&mut effects.animate, "Animate between intervals")
+        .changed();
+    changed |= ui
+        .add(
+            egui::DragValue::new(&mut effects.pulse_seconds)
+                .clamp_range(0..=60)
+                .prefix("Pulse during the final ")
+                .suffix(" s"),
+        )
+        .on_hover_text("0 turns the pulse off")
+        .changed();
+    changed |= ui
+        .checkbox(&mut effects.reduce_motion, "Reduce motion")
+        .on_hover_text("Replaces flashing and pulsing with a steady border")
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut effects.intensity, 0.0..=1.0).text("Intensity"))
+        .changed();
+    changed |= ui
+        .add(
+            egui::Slider::new(&mut effects.duration_ms, 100..=3000)
+                .text("Duration")
+                .suffix(" ms"),
+        )
+        .changed();
+
+    match changed {
+        true => Some(effects),
+        false => None,
+    }
+}
+
+fn draw_edit_timer(ui: &mut egui::Ui, index: usize, timer: &Timer) -> Option<Timer> {
+    let mut color = timer.color.into();
+    let mut kind = timer.kind;
+    let mut advance = timer.advance;
+    let mut label = timer.label.clone();
+    let (mut hrs, mut mins, mut secs, mut ms) = millis_to_time(timer.millis);
     let mut changed = false;
 
     ui.horizontal(|ui| {
@@ -95,6 +743,46 @@ fn draw_edit_timer(ui: &mut egui::Ui, timer: (u64, (u8, u8, u8))) -> Option<(u64
             changed = true;
         }
 
+        egui::ComboBox::from_id_source(("timer_kind", index))
+            .selected_text(kind.label())
+            .show_ui(ui, |ui| {
+                for option in TimerKind::ALL {
+                    if ui
+                        .selectable_value(&mut kind, option, option.label())
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                }
+            });
+
+        egui::ComboBox::from_id_source(("timer_advance", index))
+            .selected_text(advance.label())
+            .show_ui(ui, |ui| {
+                for option in Advance::ALL {
+                    if ui
+                        .selectable_value(&mut advance, option, option.label())
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_text("Whether to wait for Start before or after this interval");
+
+        let label_input = egui::TextEdit::singleline(&mut label)
+            .desired_width(100.0)
+            .hint_text("Label");
+        if ui.add(label_input).changed() {
+            changed = true;
+        }
+
+        // Count-up timers run until ended by hand
+        if kind != TimerKind::Countdown {
+            return;
+        }
+
         let prefix = zpad_prefix(hrs, 2);
         let hrs_input = egui::DragValue::new(&mut hrs).suffix(" h").prefix(prefix);
 
@@ -132,7 +820,13 @@ fn draw_edit_timer(ui: &mut egui::Ui, timer: (u64, (u8, u8, u8))) -> Option<(u64
 
     if changed {
         let new_time = time_to_millis(hrs, mins, secs, ms);
-        Some((new_time, color.into()))
+        Some(Timer {
+            millis: new_time,
+            color: color.into(),
+            kind,
+            label: sanitize_label(&label),
+            advance,
+        })
     } else {
         None
     }
@@ -0,0 +1,62 @@
+use eframe::egui;
+
+use crate::internal::keybindings::{Action, KeyBindings};
+
+pub enum ShortcutsSignal {
+    StartRebind(Action),
+    Clear(Action),
+    ResetDefaults,
+    Close,
+}
+
+pub fn draw(
+    ctx: &egui::Context,
+    bindings: &KeyBindings,
+    rebinding: Option<Action>,
+) -> Option<ShortcutsSignal> {
+    let mut update = None;
+
+    egui::Window::new("Keyboard shortcuts")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            egui::Grid::new("shortcuts")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (action, binding) in bindings.iter() {
+                        ui.label(action.label());
+
+                        let text = match (rebinding == Some(action), binding) {
+                            (true, _) => "Press a key…".to_string(),
+                            (false, Some(binding)) => binding.to_string(),
+                            (false, None) => "-".to_string(),
+                        };
+                        ui.monospace(text);
+
+                        ui.horizontal(|ui| {
+                            if ui.small_button("Change").clicked() {
+                                update = Some(ShortcutsSignal::StartRebind(action));
+                            }
+                            if ui.small_button("Clear").clicked() {
+                                update = Some(ShortcutsSignal::Clear(action));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("Reset to defaults").clicked() {
+                    update = Some(ShortcutsSignal::ResetDefaults);
+                }
+                if ui.button("Close").clicked() {
+                    update = Some(ShortcutsSignal::Close);
+                }
+            });
+        });
+
+    update
+}
@@ -0,0 +1,142 @@
+use eframe::egui;
+use egui::{Color32, Rect, Vec2};
+use std::time::Duration;
+
+use crate::internal::statistics::Statistics;
+use crate::ui::format::format_total;
+
+const CHART_HEIGHT: f32 = 120.0;
+const BAR_COLOR: Color32 = Color32::from_rgb(90, 160, 230);
+
+pub fn draw(ui: &mut egui::Ui, statistics: &Statistics) {
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                ui.heading("Overview");
+                ui.label(format!("Total time: {}", format_total(statistics.total)));
+                ui.label(format!(
+                    "Current streak: {} days",
+                    statistics.current_streak
+                ));
+                ui.label(format!(
+                    "Longest streak: {} days",
+                    statistics.longest_streak
+                ));
+
+                let completion_rate = match statistics.completion_rate {
+                    Some(rate) => format!("{:.0}%", rate * 100.0),
+                    None => "-".to_string(),
+                };
+                ui.label(format!("Completion rate: {}", completion_rate));
+            });
+
+            ui.group(|ui| {
+                ui.heading("Per day");
+                let bars: Vec<_> = statistics
+                    .days
+                    .iter()
+                    .map(|(day, time)| (day.format("%d").to_string(), *time, BAR_COLOR))
+                    .collect();
+                draw_bar_chart(ui, &bars);
+            });
+
+            ui.group(|ui| {
+                ui.heading("Per week");
+                let bars: Vec<_> = statistics
+                    .weeks
+                    .iter()
+                    .map(|(week, time)| (week.format("%d %b").to_string(), *time, BAR_COLOR))
+                    .collect();
+                draw_bar_chart(ui, &bars);
+            });
+
+            ui.group(|ui| {
+                ui.heading("Per month");
+                let bars: Vec<_> = statistics
+                    .months
+                    .iter()
+                    .map(|(month, time)| (month.format("%b").to_string(), *time, BAR_COLOR))
+                    .collect();
+                draw_bar_chart(ui, &bars);
+            });
+
+            ui.group(|ui| {
+                ui.heading("Per label");
+                let bars: Vec<_> = statistics
+                    .labels
+                    .iter()
+                    .map(|(label, time)| (label.clone(), *time, BAR_COLOR))
+                    .collect();
+                draw_bar_chart(ui, &bars);
+            });
+
+            ui.group(|ui| {
+                ui.heading("Per color");
+                let bars: Vec<_> = statistics
+                    .colors
+                    .iter()
+                    .map(|(color, time)| {
+                        let label = format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2);
+                        (label, *time, Color32::from_rgb(color.0, color.1, color.2))
+                    })
+                    .collect();
+                draw_bar_chart(ui, &bars);
+            });
+        });
+    });
+}
+
+fn draw_bar_chart(ui: &mut egui::Ui, bars: &[(String, Duration, Color32)]) {
+    if bars.is_empty() {
+        ui.label("No sessions recorded yet");
+        return;
+    }
+
+    let desired_size = Vec2 {
+        x: ui.available_width(),
+        y: CHART_HEIGHT,
+    };
+    let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::hover());
+
+    let label_height = 16.0;
+    let chart = Rect::from_min_max(
+        response.rect.min,
+        response.rect.max - Vec2::new(0.0, label_height),
+    );
+    let slot_width = chart.width() / bars.len() as f32;
+    let bar_width = slot_width * 0.7;
+    let max = bars
+        .iter()
+        .map(|bar| bar.1)
+        .max()
+        .filter(|max| !max.is_zero())
+        .unwrap_or(Duration::from_secs(1));
+
+    for (i, (label, time, color)) in bars.iter().enumerate() {
+        let center_x = chart.left() + slot_width * (i as f32 + 0.5);
+        let height = chart.height() * time.div_duration_f32(max);
+        let bar = Rect::from_min_max(
+            egui::pos2(center_x - bar_width / 2.0, chart.bottom() - height),
+            egui::pos2(center_x + bar_width / 2.0, chart.bottom()),
+        );
+        painter.rect_filled(bar, 2.0, *color);
+
+        // Skip labels that would overlap their neighbours
+        if slot_width > 24.0 || i % 2 == 0 {
+            painter.text(
+                egui::pos2(center_x, chart.bottom() + 2.0),
+                egui::Align2::CENTER_TOP,
+                label,
+                egui::FontId::proportional(11.0),
+                ui.visuals().text_color(),
+            );
+        }
+    }
+
+    if let Some(pos) = response.hover_pos() {
+        let i = ((pos.x - chart.left()) / slot_width).floor() as usize;
+        if let Some((label, time, _)) = bars.get(i) {
+            response.on_hover_text(format!("{}: {}", label, format_total(*time)));
+        }
+    }
+}
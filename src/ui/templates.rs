@@ -0,0 +1,223 @@
+use eframe::egui;
+use std::time::Duration;
+
+use crate::internal::settings_parser::ProgressStyle;
+use crate::internal::templates::{
+    MAX_EXERCISES, MAX_LONG_BREAK_EVERY, MAX_ROUNDS, Template, TemplateKind, sanitize_name,
+};
+use crate::internal::timer::Timer;
+use crate::ui::format::format_duration;
+
+pub enum TemplateSignal {
+    Apply(Vec<Timer>),
+    SaveTemplate(Template),
+    SaveProfile(String, Option<ProgressStyle>),
+    LoadProfile(String),
+}
+
+pub struct TemplateEditor {
+    template: Template,
+    exercises: String,
+    profile_name: String,
+    profile_style: Option<ProgressStyle>,
+}
+
+impl TemplateEditor {
+    pub fn new() -> Self {
+        Self {
+            template: Template::new(TemplateKind::Tabata),
+            exercises: String::new(),
+            profile_name: String::new(),
+            profile_style: None,
+        }
+    }
+
+    fn select(&mut self, template: &Template) {
+        self.template = template.clone();
+        self.exercises = template.exercises.join(", ");
+    }
+
+    fn edited_template(&self) -> Template {
+        Template {
+            name: sanitize_name(&self.template.name),
+            exercises: self
+                .exercises
+                .split(',')
+                .map(sanitize_name)
+                .filter(|v| !v.is_empty())
+                .take(MAX_EXERCISES)
+                .collect(),
+            ..self.template.clone()
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ui: &mut egui::Ui,
+        templates: &[Template],
+        profiles: &[String],
+    ) -> Option<TemplateSignal> {
+        let mut update = None;
+
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                ui.heading("Templates");
+                if let Some(signal) = self.draw_template_editor(ui, templates) {
+                    update = Some(signal);
+                }
+            });
+
+            ui.group(|ui| {
+                ui.heading("Profiles");
+                if let Some(signal) = self.draw_profiles(ui, profiles) {
+                    update = Some(signal);
+                }
+            });
+        });
+
+        update
+    }
+
+    fn draw_template_editor(
+        &mut self,
+        ui: &mut egui::Ui,
+        templates: &[Template],
+    ) -> Option<TemplateSignal> {
+        let mut update = None;
+
+        egui::ComboBox::from_label("Start from")
+            .selected_text(&self.template.name)
+            .show_ui(ui, |ui| {
+                for template in templates {
+                    if ui.selectable_label(false, &template.name).clicked() {
+                        self.select(template);
+                    }
+                }
+            });
+
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut self.template.name);
+        });
+
+        let mut kind = self.template.kind;
+        egui::ComboBox::from_label("Type")
+            .selected_text(kind.label())
+            .show_ui(ui, |ui| {
+                for option in TemplateKind::ALL {
+                    ui.selectable_value(&mut kind, option, option.label());
+                }
+            });
+        if kind != self.template.kind {
+            let name = self.template.name.clone();
+            self.template = Template {
+                name,
+                ..Template::new(kind)
+            };
+        }
+
+        let template = &mut self.template;
+        ui.add(
+            egui::DragValue::new(&mut template.rounds)
+                .clamp_range(1..=MAX_ROUNDS)
+                .prefix("Rounds: "),
+        );
+        draw_duration(ui, "Work", &mut template.work_ms);
+
+        if template.kind.uses_rest() {
+            draw_duration(ui, "Rest", &mut template.rest_ms);
+        }
+
+        if template.kind.uses_step() {
+            draw_duration(ui, "Ladder step", &mut template.step_ms);
+        }
+
+        if template.kind.uses_long_break() {
+            draw_duration(ui, "Long break", &mut template.long_break_ms);
+            ui.add(
+                egui::DragValue::new(&mut template.long_break_every)
+                    .clamp_range(0..=MAX_LONG_BREAK_EVERY)
+                    .prefix("Long break every: ")
+                    .suffix(" rounds"),
+            );
+        }
+
+        if template.kind.uses_exercises() {
+            ui.horizontal(|ui| {
+                ui.label("Exercises");
+                ui.text_edit_singleline(&mut self.exercises)
+                    .on_hover_text("Comma separated, cycled through each round");
+            });
+        }
+
+        let timers = self.edited_template().expand();
+        let total_ms: u64 = timers.iter().map(|v| v.millis).sum();
+        ui.label(format!(
+            "{} intervals, {} in total",
+            timers.len(),
+            format_duration(Duration::from_millis(total_ms))
+        ));
+
+        ui.horizontal(|ui| {
+            if ui.button("Use template").clicked() {
+                update = Some(TemplateSignal::Apply(timers));
+            }
+
+            let template = self.edited_template();
+            let button = egui::Button::new("Save template");
+            if ui.add_enabled(!template.name.is_empty(), button).clicked() {
+                update = Some(TemplateSignal::SaveTemplate(template));
+            }
+        });
+
+        update
+    }
+
+    fn draw_profiles(&mut self, ui: &mut egui::Ui, profiles: &[String]) -> Option<TemplateSignal> {
+        let mut update = None;
+
+        for profile in profiles {
+            ui.horizontal(|ui| {
+                ui.label(profile);
+                if ui.button("Load").clicked() {
+                    update = Some(TemplateSignal::LoadProfile(profile.clone()));
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut self.profile_name);
+
+            let name = sanitize_name(&self.profile_name);
+            let button = egui::Button::new("Save timers as profile");
+            if ui.add_enabled(!name.is_empty(), button).clicked() {
+                update = Some(TemplateSignal::SaveProfile(name, self.profile_style));
+            }
+        });
+
+        let selected = self.profile_style.map_or("Use global", |v| v.label());
+        egui::ComboBox::from_label("Profile progress style")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.profile_style, None, "Use global");
+                for option in ProgressStyle::ALL {
+                    ui.selectable_value(&mut self.profile_style, Some(option), option.label());
+                }
+            });
+
+        update
+    }
+}
+
+fn draw_duration(ui: &mut egui::Ui, label: &str, millis: &mut u64) {
+    let mut secs = *millis / 1000;
+    let input = egui::DragValue::new(&mut secs)
+        .clamp_range(0..=24 * 3600)
+        .prefix(format!("{}: ", label))
+        .suffix(" s");
+
+    if ui.add(input).changed() {
+        *millis = secs * 1000;
+    }
+}
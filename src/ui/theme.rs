@@ -0,0 +1,105 @@
+use eframe::egui;
+use egui::{Color32, FontId, Stroke, TextStyle};
+
+use crate::internal::settings_parser::AppSettings;
+use crate::internal::theme::{Theme, contrast_ratio, contrast_text_color};
+
+// Size of the countdown text on the classic 500x500 ring,
+// stored as a named text style so every progress style can read it
+const TIMER_TEXT: &str = "timer";
+const TIMER_TEXT_SIZE: f32 = 32.0;
+
+pub fn apply(ctx: &egui::Context, settings: &AppSettings) {
+    let visuals = match settings.theme {
+        Theme::Dark => egui::Visuals::dark(),
+        Theme::Light => egui::Visuals::light(),
+        Theme::HighContrast => high_contrast(),
+    };
+
+    let mut style = (*ctx.style()).clone();
+    style.visuals = visuals;
+
+    let body = settings.font_size;
+    style.text_styles = [
+        (TextStyle::Small, FontId::proportional(body * 0.7)),
+        (TextStyle::Body, FontId::proportional(body)),
+        (TextStyle::Button, FontId::proportional(body)),
+        (TextStyle::Monospace, FontId::monospace(body)),
+        (TextStyle::Heading, FontId::proportional(body * 1.5)),
+        (
+            TextStyle::Name(TIMER_TEXT.into()),
+            FontId::proportional(TIMER_TEXT_SIZE * settings.timer_font_scale),
+        ),
+    ]
+    .into();
+
+    // Only touch the context when something changed, egui relayouts otherwise
+    if *ctx.style() != style {
+        ctx.set_style(style);
+    }
+}
+
+pub fn timer_font_scale(style: &egui::Style) -> f32 {
+    match style.text_styles.get(&TextStyle::Name(TIMER_TEXT.into())) {
+        Some(font) => font.size / TIMER_TEXT_SIZE,
+        None => 1.0,
+    }
+}
+
+// The color under the text, as it looks on the panel
+pub fn text_color_on(style: &egui::Style, background: Color32) -> Color32 {
+    let panel = style.visuals.panel_fill;
+    let [r, g, b, a] = background.to_array();
+    let blend =
+        |top: u8, bottom: u8| top.saturating_add(((bottom as u16 * (255 - a as u16)) / 255) as u8);
+    let (r, g, b) = contrast_text_color((
+        blend(r, panel.r()),
+        blend(g, panel.g()),
+        blend(b, panel.b()),
+    ));
+    Color32::from_rgb(r, g, b)
+}
+
+// Interval colored text, unless it would vanish into the panel
+pub fn readable_on_panel(style: &egui::Style, color: Color32) -> Color32 {
+    let panel = style.visuals.panel_fill;
+    let ratio = contrast_ratio(
+        (color.r(), color.g(), color.b()),
+        (panel.r(), panel.g(), panel.b()),
+    );
+    match ratio < 3.0 {
+        true => style.visuals.text_color(),
+        false => color,
+    }
+}
+
+fn high_contrast() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::from_gray(20);
+    visuals.selection.bg_fill = Color32::from_rgb(0, 90, 200);
+    visuals.selection.stroke = Stroke::new(2.0, Color32::WHITE);
+    visuals.hyperlink_color = Color32::from_rgb(255, 255, 0);
+
+    let widgets = &mut visuals.widgets;
+    for state in [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ] {
+        state.bg_stroke = Stroke::new(1.5, Color32::WHITE);
+        state.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+    }
+    widgets.noninteractive.bg_fill = Color32::BLACK;
+    widgets.inactive.bg_fill = Color32::from_gray(30);
+    widgets.inactive.weak_bg_fill = Color32::from_gray(30);
+    widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::from_rgb(255, 255, 0));
+    widgets.active.bg_stroke = Stroke::new(2.5, Color32::from_rgb(255, 255, 0));
+
+    visuals
+}
@@ -0,0 +1,173 @@
+use chrono::{Local, TimeZone};
+use eframe::egui;
+
+use crate::internal::templates::sanitize_name;
+use crate::internal::webhooks::{Delivery, DeliveryStatus, Webhook, WebhookEvent};
+
+pub enum WebhookSignal {
+    Save(Webhook),
+    Delete(String),
+    Test(String),
+}
+
+pub struct WebhookEditor {
+    webhook: Webhook,
+}
+
+const TEMPLATE_HINT: &str = r#"{"workout":"{profile}","done":"{label}","ms":{elapsed_ms}}"#;
+
+impl WebhookEditor {
+    pub fn new() -> Self {
+        Self {
+            webhook: Webhook::default(),
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ui: &mut egui::Ui,
+        webhooks: &[Webhook],
+        log: &[Delivery],
+        error: Option<&str>,
+    ) -> Option<WebhookSignal> {
+        let mut update = None;
+
+        ui.vertical_centered_justified(|ui| {
+            ui.group(|ui| {
+                ui.heading("Webhooks");
+                for webhook in webhooks {
+                    ui.horizontal(|ui| {
+                        ui.label(&webhook.name);
+                        ui.weak(&webhook.url);
+                        if ui.button("Edit").clicked() {
+                            self.webhook = webhook.clone();
+                        }
+                        if ui.button("Test").clicked() {
+                            update = Some(WebhookSignal::Test(webhook.name.clone()));
+                        }
+                        if ui.button("Delete").clicked() {
+                            update = Some(WebhookSignal::Delete(webhook.name.clone()));
+                        }
+                    });
+                }
+
+                ui.separator();
+                if let Some(signal) = self.draw_editor(ui) {
+                    update = Some(signal);
+                }
+                if let Some(error) = error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            });
+
+            ui.group(|ui| {
+                ui.heading("Deliveries");
+                draw_log(ui, log);
+            });
+        });
+
+        update
+    }
+
+    fn draw_editor(&mut self, ui: &mut egui::Ui) -> Option<WebhookSignal> {
+        let mut update = None;
+        let webhook = &mut self.webhook;
+
+        egui::Grid::new("webhook").num_columns(2).show(ui, |ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut webhook.name);
+            ui.end_row();
+
+            ui.label("URL");
+            let input = egui::TextEdit::singleline(&mut webhook.url)
+                .hint_text("http://localhost:8080/workouts");
+            ui.add(input);
+            ui.end_row();
+        });
+
+        ui.horizontal_wrapped(|ui| {
+            for event in WebhookEvent::ALL {
+                let mut selected = webhook.events.contains(&event);
+                if ui.checkbox(&mut selected, event.label()).changed() {
+                    webhook.events.retain(|v| *v != event);
+                    if selected {
+                        webhook.events.push(event);
+                    }
+                }
+            }
+        });
+
+        ui.label("Payload");
+        ui.add(
+            egui::TextEdit::multiline(&mut webhook.template)
+                .code_editor()
+                .desired_rows(3)
+                .hint_text(TEMPLATE_HINT),
+        )
+        .on_hover_text(
+            "Empty sends every field as JSON. Available fields are {event}, {profile}, \
+             {label}, {index}, {count}, {round}, {duration_ms}, {elapsed_ms}, \
+             {session_elapsed_ms} and {timestamp}",
+        );
+
+        ui.horizontal(|ui| {
+            let name = sanitize_name(&webhook.name);
+            let button = egui::Button::new("Save webhook");
+            if ui.add_enabled(!name.is_empty(), button).clicked() {
+                let mut events = webhook.events.clone();
+                events.sort_by_key(|event| WebhookEvent::ALL.iter().position(|v| v == event));
+                update = Some(WebhookSignal::Save(Webhook {
+                    name,
+                    events,
+                    ..webhook.clone()
+                }));
+            }
+
+            if ui.button("New").clicked() {
+                *webhook = Webhook::default();
+            }
+        });
+
+        update
+    }
+}
+
+fn draw_log(ui: &mut egui::Ui, log: &[Delivery]) {
+    if log.is_empty() {
+        ui.weak("Nothing sent yet");
+        return;
+    }
+
+    egui::Grid::new("webhook_log")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for delivery in log {
+                let time = Local
+                    .timestamp_opt(delivery.sent_at, 0)
+                    .single()
+                    .map(|v| v.format("%H:%M:%S").to_string())
+                    .unwrap_or_default();
+                ui.monospace(time);
+                ui.label(&delivery.webhook);
+                ui.label(delivery.event.label());
+
+                let attempts = match delivery.attempts {
+                    1 => String::new(),
+                    n => format!(" after {} attempts", n),
+                };
+                match &delivery.status {
+                    DeliveryStatus::Pending => ui.weak("Sending"),
+                    DeliveryStatus::Retrying(e) => ui.label(format!("Retrying: {}", e)),
+                    DeliveryStatus::Delivered(code) => {
+                        ui.label(format!("Delivered ({}){}", code, attempts))
+                    }
+                    DeliveryStatus::Failed(e) => ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("Failed{}: {}", attempts, e),
+                    ),
+                };
+                ui.end_row();
+            }
+        });
+}
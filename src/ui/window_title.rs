@@ -0,0 +1,85 @@
+use eframe::egui;
+use egui::{Color32, IconData, ViewportCommand};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::sync::Arc;
+
+use crate::internal::title::{TitleFields, format_title};
+
+const APP_TITLE: &str = "Interval Timer";
+const ICON_SIZE: u32 = 32;
+// Icon redraws per interval, each one is a full image upload
+const ICON_STEPS: f32 = 32.0;
+
+pub struct WindowTitle {
+    title: String,
+    icon: Option<(Color32, u32)>,
+}
+
+impl WindowTitle {
+    pub fn new() -> Self {
+        Self {
+            title: APP_TITLE.to_string(),
+            icon: None,
+        }
+    }
+
+    // Only sends commands when the title or icon would look different
+    pub fn update(
+        &mut self,
+        ctx: &egui::Context,
+        format: &str,
+        fields: Option<TitleFields>,
+        color: Color32,
+    ) {
+        let title = match &fields {
+            Some(fields) => format_title(format, fields),
+            None => APP_TITLE.to_string(),
+        };
+        if title != self.title {
+            ctx.send_viewport_cmd(ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+
+        let icon = fields.map(|v| (color, (v.fraction * ICON_STEPS).round() as u32));
+        if icon != self.icon {
+            let data =
+                icon.map(|(color, step)| Arc::new(draw_icon(color, step as f32 / ICON_STEPS)));
+            ctx.send_viewport_cmd(ViewportCommand::Icon(data));
+            self.icon = icon;
+        }
+    }
+}
+
+// A small pie of the current interval, filled clockwise from the top
+fn draw_icon(color: Color32, frac: f32) -> IconData {
+    let size = ICON_SIZE as f32;
+    let center = size / 2.0;
+    let radius = center - 1.0;
+    let dim = color.linear_multiply(0.3);
+
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let dx = x as f32 + 0.5 - center;
+            let dy = y as f32 + 0.5 - center;
+
+            let pixel = match (dx * dx + dy * dy).sqrt() <= radius {
+                false => Color32::TRANSPARENT,
+                true => {
+                    let angle = (dy.atan2(dx) + FRAC_PI_2).rem_euclid(TAU);
+                    match angle <= frac * TAU {
+                        true => color,
+                        false => dim,
+                    }
+                }
+            };
+            rgba.extend_from_slice(&pixel.to_srgba_unmultiplied());
+        }
+    }
+
+    IconData {
+        rgba,
+        width: ICON_SIZE,
+        height: ICON_SIZE,
+    }
+}